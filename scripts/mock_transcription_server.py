@@ -0,0 +1,41 @@
+#!/usr/bin/env python3
+"""Minimal OpenAI-compatible transcription server for trying the remote engine.
+
+Answers POST /v1/audio/transcriptions and /v1/audio/translations with a fixed text
+and logs the form fields it received (the audio itself is not decoded).
+
+    python3 scripts/mock_transcription_server.py [port]
+"""
+import json
+import sys
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+
+class Handler(BaseHTTPRequestHandler):
+    def do_POST(self):
+        if self.path not in ("/v1/audio/transcriptions", "/v1/audio/translations"):
+            self.send_error(404)
+            return
+        length = int(self.headers.get("Content-Length", 0))
+        body = self.rfile.read(length)
+        print(f"{self.path}: {length} bytes, auth={self.headers.get('Authorization')!r}")
+        for field in (b"model", b"language", b"response_format"):
+            marker = b'name="' + field + b'"\r\n\r\n'
+            start = body.find(marker)
+            if start >= 0:
+                value = body[start + len(marker):body.find(b"\r\n", start + len(marker))]
+                print(f"  {field.decode()} = {value.decode(errors='replace')}")
+
+        kind = "translation" if self.path.endswith("translations") else "transcription"
+        payload = json.dumps({"text": f"Hello from the mock {kind} server."}).encode()
+        self.send_response(200)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Content-Length", str(len(payload)))
+        self.end_headers()
+        self.wfile.write(payload)
+
+
+if __name__ == "__main__":
+    port = int(sys.argv[1]) if len(sys.argv) > 1 else 8000
+    print(f"Mock transcription server on http://127.0.0.1:{port}/v1")
+    HTTPServer(("127.0.0.1", port), Handler).serve_forever()
@@ -0,0 +1,66 @@
+#!/usr/bin/env python3
+"""Pin the SHA-256 of every model in src/model_registry.rs.
+
+Reads the checksums of the Git LFS files in the upstream whisper.cpp model repository
+from the Hugging Face API and writes them into the registry as `.pinned("...")`.
+Entries whose file is not published upstream are reported and left alone.
+
+    python3 scripts/update_model_checksums.py [path/to/model_registry.rs]
+
+With --check nothing is downloaded or written; the script lists entries without a pinned
+checksum and exits with status 1 if there are any.
+"""
+import json
+import re
+import sys
+import urllib.request
+
+API_URL = "https://huggingface.co/api/models/ggerganov/whisper.cpp/tree/main"
+ENTRY = re.compile(
+    r'^(?P<indent>\s*)(?P<call>(?:model|english)\("[^"]+", "(?P<file>[^"]+)"[^)]*\))'
+    r'(?:\.pinned\("[0-9a-f]*"\))?,$'
+)
+
+
+def upstream_checksums():
+    with urllib.request.urlopen(API_URL, timeout=60) as response:
+        files = json.load(response)
+    return {f["path"]: f["lfs"]["oid"] for f in files if f.get("lfs")}
+
+
+def check(lines):
+    unpinned = [m["file"] for m in map(ENTRY.match, lines) if m and ".pinned(" not in m[0]]
+    for file in unpinned:
+        print(f"{file}: no pinned checksum", file=sys.stderr)
+    return 1 if unpinned else 0
+
+
+def main():
+    args = [a for a in sys.argv[1:] if a != "--check"]
+    path = args[0] if args else "src/model_registry.rs"
+    with open(path, encoding="utf-8") as f:
+        lines = f.read().split("\n")
+    if "--check" in sys.argv[1:]:
+        sys.exit(check(lines))
+
+    checksums = upstream_checksums()
+
+    pinned = 0
+    for i, line in enumerate(lines):
+        match = ENTRY.match(line)
+        if not match:
+            continue
+        sha = checksums.get(match["file"])
+        if sha is None:
+            print(f"{match['file']}: not published upstream", file=sys.stderr)
+            continue
+        lines[i] = f'{match["indent"]}{match["call"]}.pinned("{sha}"),'
+        pinned += 1
+
+    with open(path, "w", encoding="utf-8") as f:
+        f.write("\n".join(lines))
+    print(f"Pinned {pinned} checksums in {path}")
+
+
+if __name__ == "__main__":
+    main()
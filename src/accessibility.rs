@@ -0,0 +1,269 @@
+use atspi::proxy::accessible::{AccessibleProxy, ObjectRefExt};
+use atspi::proxy::editable_text::EditableTextProxy;
+use atspi::proxy::text::TextProxy;
+use atspi::zbus::{self, proxy::CacheProperties};
+use atspi::{AccessibilityConnection, Interface, State};
+
+use crate::insertion::InsertError;
+use std::future::Future;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+const REGISTRY: &str = "org.a11y.atspi.Registry";
+const ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+
+// An unresponsive application must not hold up insertion
+const QUERY_TIMEOUT: Duration = Duration::from_millis(400);
+
+// Inserting waits longer: giving up midway could insert the text twice after fallback
+const INSERT_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Upper bound on objects visited while looking for the focused one
+const MAX_NODES: usize = 3000;
+
+/// Run `query` on the accessibility bus in a helper thread.
+/// None when the bus is unavailable, the query fails or it takes longer than `timeout`.
+fn with_accessibility_bus<T, F, Fut>(timeout: Duration, query: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce(zbus::Connection) -> Fut + Send + 'static,
+    Fut: Future<Output = Option<T>>,
+{
+    run_on_bus(timeout, query).ok().flatten()
+}
+
+// Like `with_accessibility_bus`, but a timeout is told apart from an unavailable bus:
+// the query is still running then and may yet take effect
+fn run_on_bus<T, F, Fut>(timeout: Duration, query: F) -> Result<Option<T>, RecvTimeoutError>
+where
+    T: Send + 'static,
+    F: FnOnce(zbus::Connection) -> Fut + Send + 'static,
+    Fut: Future<Output = Option<T>>,
+{
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread().build() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                eprintln!("Failed to start accessibility query: {}", e);
+                return;
+            }
+        };
+        let result = runtime.block_on(async move {
+            let connection = AccessibilityConnection::new().await.ok()?;
+            query(connection.connection().clone()).await
+        });
+        let _ = tx.send(result);
+    });
+    match rx.recv_timeout(timeout) {
+        Err(RecvTimeoutError::Disconnected) => Ok(None),
+        result => result,
+    }
+}
+
+// The application owning the active window, and that window
+async fn active_window(
+    conn: &zbus::Connection,
+) -> Option<(AccessibleProxy<'_>, AccessibleProxy<'_>)> {
+    let root = AccessibleProxy::builder(conn)
+        .cache_properties(CacheProperties::No)
+        .destination(REGISTRY)
+        .ok()?
+        .path(ROOT_PATH)
+        .ok()?
+        .build()
+        .await
+        .ok()?;
+    for app in root.get_children().await.ok()? {
+        let Ok(app) = app.into_accessible_proxy(conn).await else {
+            continue;
+        };
+        for window in app.get_children().await.unwrap_or_default() {
+            let Ok(window) = window.into_accessible_proxy(conn).await else {
+                continue;
+            };
+            let active = window
+                .get_state()
+                .await
+                .is_ok_and(|state| state.contains(State::Active));
+            if active {
+                return Some((app, window));
+            }
+        }
+    }
+    None
+}
+
+// The focused object in the active window of any application
+async fn focused_object(conn: &zbus::Connection) -> Option<AccessibleProxy<'_>> {
+    let (_, window) = active_window(conn).await?;
+    find_focused(conn, window).await
+}
+
+// Depth-first search below `window`, skipping hidden subtrees and long lists
+async fn find_focused<'c>(
+    conn: &'c zbus::Connection,
+    window: AccessibleProxy<'c>,
+) -> Option<AccessibleProxy<'c>> {
+    let mut pending = vec![window];
+    let mut visited = 0;
+    while let Some(object) = pending.pop() {
+        visited += 1;
+        if visited > MAX_NODES {
+            break;
+        }
+        let Ok(state) = object.get_state().await else {
+            continue;
+        };
+        if state.contains(State::Focused) {
+            return Some(object);
+        }
+        if !state.contains(State::Showing) || state.contains(State::ManagesDescendants) {
+            continue;
+        }
+        for child in object.get_children().await.unwrap_or_default() {
+            if let Ok(child) = child.into_accessible_proxy(conn).await {
+                pending.push(child);
+            }
+        }
+    }
+    None
+}
+
+// The Text interface of `object`, if it is an editable text widget
+async fn text_interface<'c>(
+    conn: &'c zbus::Connection,
+    object: &AccessibleProxy<'c>,
+) -> Option<TextProxy<'c>> {
+    let state = object.get_state().await.ok()?;
+    let interfaces = object.get_interfaces().await.ok()?;
+    if !state.contains(State::Editable) || !interfaces.contains(Interface::Text) {
+        return None;
+    }
+    TextProxy::builder(conn)
+        .cache_properties(CacheProperties::No)
+        .destination(object.inner().destination().clone())
+        .ok()?
+        .path(object.inner().path().clone())
+        .ok()?
+        .build()
+        .await
+        .ok()
+}
+
+// The EditableText interface of `object`, if it has one
+async fn editable_text_interface<'c>(
+    conn: &'c zbus::Connection,
+    object: &AccessibleProxy<'c>,
+) -> Option<EditableTextProxy<'c>> {
+    let interfaces = object.get_interfaces().await.ok()?;
+    if !interfaces.contains(Interface::EditableText) {
+        return None;
+    }
+    EditableTextProxy::builder(conn)
+        .cache_properties(CacheProperties::No)
+        .destination(object.inner().destination().clone())
+        .ok()?
+        .path(object.inner().path().clone())
+        .ok()?
+        .build()
+        .await
+        .ok()
+}
+
+/// Name of the application owning the active window (e.g. "gedit", "Firefox").
+/// None when AT-SPI is not available or no accessible window is active.
+pub fn focused_application_name() -> Option<String> {
+    with_accessibility_bus(QUERY_TIMEOUT, |conn| async move {
+        let (app, _) = active_window(&conn).await?;
+        app.name().await.ok().filter(|name| !name.is_empty())
+    })
+}
+
+/// Up to `max_chars` characters before the caret in the focused editable text widget.
+/// None when there is no such widget or AT-SPI is not available.
+pub fn text_before_caret(max_chars: usize) -> Option<String> {
+    with_accessibility_bus(QUERY_TIMEOUT, move |conn| async move {
+        let object = focused_object(&conn).await?;
+        let text = text_interface(&conn, &object).await?;
+        let caret = text.caret_offset().await.ok()?;
+        if caret < 0 {
+            return None;
+        }
+        let start = caret.saturating_sub(max_chars as i32).max(0);
+        text.get_text(start, caret).await.ok()
+    })
+}
+
+/// Insert `text` into the focused editable text widget at the caret, replacing the selection,
+/// and check that it arrived by reading it back. Fails without changing anything when
+/// there is no editable widget or AT-SPI is not available.
+pub fn insert_at_caret(text: &str) -> Result<(), InsertError> {
+    let text = text.to_string();
+    match run_on_bus(INSERT_TIMEOUT, move |conn| async move {
+        Some(insert_into_focused(&conn, &text).await)
+    }) {
+        Ok(Some(result)) => result,
+        Ok(None) => Err(InsertError::Failed("AT-SPI is not available".to_string())),
+        // The insertion may still complete in the application
+        Err(_) => Err(InsertError::Partial(
+            "AT-SPI did not respond in time; the text may have been inserted".to_string(),
+        )),
+    }
+}
+
+async fn insert_into_focused(conn: &zbus::Connection, text: &str) -> Result<(), InsertError> {
+    let failed = |e: &str| InsertError::Failed(e.to_string());
+    let object = focused_object(conn)
+        .await
+        .ok_or_else(|| failed("No focused accessible widget"))?;
+    let (Some(reader), Some(editor)) = (
+        text_interface(conn, &object).await,
+        editable_text_interface(conn, &object).await,
+    ) else {
+        return Err(failed("The focused widget is not editable text"));
+    };
+    let error = |e: zbus::Error| InsertError::Failed(format!("AT-SPI call failed: {}", e));
+
+    let mut position = reader.caret_offset().await.map_err(error)?;
+    if position < 0 {
+        return Err(failed("The focused widget has no caret"));
+    }
+    let mut selection = None;
+    if reader.get_nselections().await.unwrap_or(0) > 0 {
+        let (start, end) = reader.get_selection(0).await.map_err(error)?;
+        if start < end {
+            selection = Some((start, end));
+            position = start;
+        }
+    }
+
+    let count_before = reader.character_count().await.map_err(error)?;
+    let length = text.chars().count() as i32;
+    // The widget may have applied the call even if the reply got lost
+    let accepted = editor
+        .insert_text(position, text, length)
+        .await
+        .map_err(|e| InsertError::Partial(format!("AT-SPI call failed: {}", e)))?;
+    if !accepted {
+        return Err(failed("The widget refused the text"));
+    }
+
+    let inserted = reader.get_text(position, position + length).await;
+    if inserted.as_deref() != Ok(text) {
+        if reader.character_count().await.ok() == Some(count_before) {
+            return Err(failed("The text did not appear in the widget"));
+        }
+        // Something arrived (widgets may rewrite line breaks or quotes); don't insert it twice
+        eprintln!("Inserted text reads back differently; not inserting it again");
+    }
+    // The selection is replaced only once the text is in, and now follows it
+    if let Some((start, end)) = selection {
+        if let Err(e) = editor.delete_text(start + length, end + length).await {
+            eprintln!("Failed to remove the selected text: {}", e);
+        }
+    }
+    let _ = reader.set_caret_offset(position + length).await;
+    Ok(())
+}
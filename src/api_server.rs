@@ -0,0 +1,461 @@
+use std::fs::File;
+use std::io::Read;
+use std::sync::Arc;
+use std::thread;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::audio_utils;
+use crate::config;
+use crate::model_cache::ModelCache;
+use crate::transcriber_utils::{
+    ensure_transcriber_for, select_model_file_for_power, transcribe_samples_with,
+    transcription_slot, translate_samples_with,
+};
+
+// Uploads larger than this are rejected (about 50 minutes of 16 kHz 16-bit mono WAV)
+const MAX_UPLOAD_BYTES: usize = 100 * 1024 * 1024;
+
+// Fields of a multipart/form-data request
+struct FormData {
+    file: Option<Vec<u8>>,
+    language: Option<String>,
+    response_format: Option<String>,
+}
+
+/// Start the local OpenAI-compatible transcription API on 127.0.0.1 if enabled in config.
+/// It transcribes with the models in `model_cache`, queued with hotkey dictation.
+pub fn start_if_enabled(model_cache: Arc<ModelCache>) {
+    let settings = config::get_api_server();
+    if !settings.enabled {
+        return;
+    }
+
+    let token = if settings.token.trim().is_empty() {
+        let token = match generate_token() {
+            Ok(token) => token,
+            Err(e) => {
+                eprintln!(
+                    "Failed to generate an API token, not starting the API: {}",
+                    e
+                );
+                return;
+            }
+        };
+        if let Err(e) = config::save_api_server_token(&token) {
+            eprintln!("Failed to save API token to config: {}", e);
+        }
+        println!("Generated API token (saved in config.json): {}", token);
+        token
+    } else {
+        settings.token.trim().to_string()
+    };
+
+    let addr = format!("127.0.0.1:{}", settings.port);
+    let server = match Server::http(&addr) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Failed to start transcription API on {}: {}", addr, e);
+            return;
+        }
+    };
+    println!("Transcription API listening on http://{}/v1", addr);
+
+    // Requests are handled one at a time; each waits for the model like a hotkey dictation
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            handle_request(request, &token, &model_cache);
+        }
+    });
+}
+
+// 128 random bits from the OS as 32 hex digits
+fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .map_err(|e| format!("Failed to read /dev/urandom: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// Compare without returning early, so response times don't reveal how much of the token matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn header_value<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.as_str())
+}
+
+fn respond(request: Request, status: u16, content_type: &str, body: String) {
+    let header = Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes())
+        .expect("static header is valid");
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header);
+    if let Err(e) = request.respond(response) {
+        eprintln!("Failed to send API response: {}", e);
+    }
+}
+
+// Errors use the OpenAI shape: {"error": {"message": ...}}
+fn respond_error(request: Request, status: u16, message: &str) {
+    let body = serde_json::json!({ "error": { "message": message } }).to_string();
+    respond(request, status, "application/json", body);
+}
+
+fn handle_request(mut request: Request, token: &str, model_cache: &ModelCache) {
+    let route = match (request.method(), request.url()) {
+        (Method::Post, "/v1/audio/transcriptions") => Some(false),
+        (Method::Post, "/v1/audio/translations") => Some(true),
+        _ => None,
+    };
+    let Some(translate) = route else {
+        return respond_error(request, 404, "Not found");
+    };
+
+    let authorized = header_value(&request, "Authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|t| constant_time_eq(t.trim().as_bytes(), token.as_bytes()));
+    if !authorized {
+        return respond_error(request, 401, "Missing or invalid bearer token");
+    }
+
+    let boundary = match header_value(&request, "Content-Type").and_then(multipart_boundary) {
+        Some(b) => b,
+        None => return respond_error(request, 400, "Expected multipart/form-data"),
+    };
+    let mut body = Vec::new();
+    let read = request
+        .as_reader()
+        .take(MAX_UPLOAD_BYTES as u64 + 1)
+        .read_to_end(&mut body);
+    if let Err(e) = read {
+        return respond_error(request, 400, &format!("Failed to read request: {}", e));
+    }
+    if body.len() > MAX_UPLOAD_BYTES {
+        return respond_error(request, 413, "Audio file is too large");
+    }
+
+    let form = parse_multipart(&body, &boundary);
+    let Some(file) = form.file else {
+        return respond_error(request, 400, "Missing 'file' field");
+    };
+    let (samples, sample_rate, channels) = match audio_utils::decode_wav(&file) {
+        Ok(audio) => audio,
+        Err(e) => return respond_error(request, 400, &format!("Only WAV audio is supported: {}", e)),
+    };
+
+    // Same model selection as dictation: the selected model, English-only variant for English
+    let language = form.language.filter(|l| !l.is_empty());
+    let is_english = language.as_deref().is_some_and(|l| l.starts_with("en"));
+    let model_file = select_model_file_for_power(&config::get_selected_model(), is_english);
+
+    let result = {
+        let _slot = transcription_slot();
+        ensure_transcriber_for(is_english, &model_file, model_cache).and_then(|transcriber| {
+            if translate {
+                translate_samples_with(
+                    &*transcriber,
+                    &samples,
+                    sample_rate,
+                    channels,
+                    language.as_deref().unwrap_or(""),
+                )
+            } else if let Some(lang) = language.as_deref() {
+                transcribe_samples_with(&*transcriber, &samples, sample_rate, channels, lang)
+            } else {
+                // No language: let whisper detect it
+                transcriber.transcribe_samples(&samples, sample_rate, channels, None)
+            }
+        })
+    };
+
+    match result {
+        Ok(text) => {
+            let text = text.trim().to_string();
+            match form.response_format.as_deref() {
+                Some("text") => respond(request, 200, "text/plain; charset=utf-8", text),
+                _ => respond(
+                    request,
+                    200,
+                    "application/json",
+                    serde_json::json!({ "text": text }).to_string(),
+                ),
+            }
+        }
+        Err(e) => {
+            eprintln!("API transcription failed: {}", e);
+            respond_error(request, 500, &e)
+        }
+    }
+}
+
+// Media type and parameter names are case-insensitive; the boundary may be quoted
+fn multipart_boundary(content_type: &str) -> Option<String> {
+    let mut params = content_type.split(';');
+    if !params
+        .next()?
+        .trim()
+        .eq_ignore_ascii_case("multipart/form-data")
+    {
+        return None;
+    }
+    params
+        .filter_map(|p| p.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+        .filter(|b| !b.is_empty())
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if from >= haystack.len() {
+        return None;
+    }
+    haystack[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|p| p + from)
+}
+
+// Minimal multipart/form-data parser for the fields the API uses
+fn parse_multipart(body: &[u8], boundary: &str) -> FormData {
+    let mut form = FormData {
+        file: None,
+        language: None,
+        response_format: None,
+    };
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut pos = match find(body, &delimiter, 0) {
+        Some(p) => p + delimiter.len(),
+        None => return form,
+    };
+
+    loop {
+        // "--" after a delimiter ends the body
+        if body[pos..].starts_with(b"--") {
+            break;
+        }
+        let Some(headers_end) = find(body, b"\r\n\r\n", pos) else {
+            break;
+        };
+        let Some(next) = find(body, &delimiter, headers_end) else {
+            break;
+        };
+        let headers = String::from_utf8_lossy(&body[pos..headers_end]);
+        // Content ends with the CRLF that precedes the next delimiter
+        let content_end = next.saturating_sub(2).max(headers_end + 4);
+        let content = &body[headers_end + 4..content_end];
+
+        let name = headers
+            .lines()
+            .find(|l| l.to_ascii_lowercase().starts_with("content-disposition:"))
+            .and_then(|l| {
+                l.split(';')
+                    .filter_map(|p| p.trim().strip_prefix("name="))
+                    .map(|n| n.trim_matches('"').to_string())
+                    .next()
+            });
+        match name.as_deref() {
+            Some("file") => form.file = Some(content.to_vec()),
+            Some("language") => form.language = Some(String::from_utf8_lossy(content).trim().to_string()),
+            Some("response_format") => {
+                form.response_format = Some(String::from_utf8_lossy(content).trim().to_string())
+            }
+            _ => {}
+        }
+        pos = next + delimiter.len();
+    }
+    form
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_cache::CacheSettings;
+    use std::io::Write;
+    use std::net::TcpStream;
+
+    #[test]
+    fn boundaries() {
+        let cases = [
+            ("multipart/form-data; boundary=abc123", Some("abc123")),
+            (
+                "multipart/form-data; boundary=\"----Web Kit\"",
+                Some("----Web Kit"),
+            ),
+            ("multipart/form-data;boundary=\"a=b\"", Some("a=b")),
+            (
+                "Multipart/Form-Data; charset=utf-8; Boundary=xyz",
+                Some("xyz"),
+            ),
+            ("multipart/form-data", None),
+            ("multipart/form-data; boundary=", None),
+            ("multipart/form-data; boundary=\"\"", None),
+            ("multipart/mixed; boundary=abc", None),
+            ("application/json", None),
+        ];
+        for (content_type, expected) in cases {
+            assert_eq!(
+                multipart_boundary(content_type).as_deref(),
+                expected,
+                "{}",
+                content_type
+            );
+        }
+    }
+
+    // (field name, extra Content-Disposition parameters, content)
+    fn multipart(parts: &[(&str, &str, &[u8])], close: bool) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (name, extra, content) in parts {
+            body.extend_from_slice(b"--BOUNDARY\r\n");
+            body.extend_from_slice(
+                format!(
+                    "Content-Disposition: form-data; name=\"{}\"{}\r\n",
+                    name, extra
+                )
+                .as_bytes(),
+            );
+            if *name == "file" {
+                body.extend_from_slice(b"Content-Type: audio/wav\r\n");
+            }
+            body.extend_from_slice(b"\r\n");
+            body.extend_from_slice(content);
+            body.extend_from_slice(b"\r\n");
+        }
+        if close {
+            body.extend_from_slice(b"--BOUNDARY--\r\n");
+        }
+        body
+    }
+
+    #[test]
+    fn multipart_forms() {
+        let wav: &[u8] = b"RIFF\r\n--BOUND\r\n\r\ndata\r\n";
+        let form = parse_multipart(
+            &multipart(
+                &[
+                    ("model", "", b"whisper-1"),
+                    ("file", "; filename=\"a.wav\"", wav),
+                    ("language", "", b" de "),
+                    ("response_format", "", b"text"),
+                ],
+                true,
+            ),
+            "BOUNDARY",
+        );
+        // CRLFs inside the payload are kept
+        assert_eq!(form.file.as_deref(), Some(wav));
+        assert_eq!(form.language.as_deref(), Some("de"));
+        assert_eq!(form.response_format.as_deref(), Some("text"));
+
+        let form = parse_multipart(&multipart(&[("file", "", b"")], true), "BOUNDARY");
+        assert_eq!(form.file.as_deref(), Some(&b""[..]));
+
+        // Without the final delimiter the last part is incomplete and dropped
+        let form = parse_multipart(
+            &multipart(&[("language", "", b"en"), ("file", "", b"RIFF")], false),
+            "BOUNDARY",
+        );
+        assert_eq!(form.language.as_deref(), Some("en"));
+        assert_eq!(form.file, None);
+
+        let truncated: [&[u8]; 4] = [
+            b"",
+            b"no delimiter here",
+            b"--BOUNDARY",
+            b"--BOUNDARY\r\nno blank line",
+        ];
+        for body in truncated {
+            let form = parse_multipart(body, "BOUNDARY");
+            assert!(form.file.is_none() && form.language.is_none(), "{:?}", body);
+        }
+    }
+
+    #[test]
+    fn token_comparison() {
+        let cases: [(&[u8], &[u8], bool); 5] = [
+            (b"secret", b"secret", true),
+            (b"secret", b"secreT", false),
+            (b"secret", b"secre", false),
+            (b"", b"", true),
+            (b"", b"secret", false),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(constant_time_eq(a, b), expected, "{:?} {:?}", a, b);
+        }
+    }
+
+    // Status of a request with `authorization` to an API whose token is "secret"
+    fn status_for(url: &str, authorization: Option<&str>) -> String {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        let handler = thread::spawn(move || {
+            let cache = ModelCache::new(CacheSettings {
+                ram_budget_bytes: 0,
+                vram_budget_bytes: 0,
+                idle_timeout: None,
+                warm_up: false,
+            });
+            let request = server.recv().unwrap();
+            handle_request(request, "secret", &cache);
+        });
+
+        // A form without a file: accepted requests stop there, before any model is loaded
+        let body = multipart(&[("language", "", b"en")], true);
+        let mut head = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\
+             Content-Type: multipart/form-data; boundary=BOUNDARY\r\nContent-Length: {}\r\n",
+            url,
+            addr,
+            body.len()
+        );
+        if let Some(value) = authorization {
+            head.push_str(&format!("Authorization: {}\r\n", value));
+        }
+        head.push_str("\r\n");
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(head.as_bytes()).unwrap();
+        stream.write_all(&body).unwrap();
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response);
+        handler.join().unwrap();
+        response
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    #[test]
+    fn bearer_token() {
+        let transcriptions = "/v1/audio/transcriptions";
+        let cases = [
+            (transcriptions, None, "401"),
+            (transcriptions, Some("Bearer wrong"), "401"),
+            (transcriptions, Some("Bearer secre"), "401"),
+            (transcriptions, Some("Bearer secret2"), "401"),
+            (transcriptions, Some("Basic secret"), "401"),
+            (transcriptions, Some("secret"), "401"),
+            (transcriptions, Some("Bearer secret"), "400"),
+            ("/v1/audio/translations", Some("Bearer secret"), "400"),
+            ("/v1/models", Some("Bearer secret"), "404"),
+        ];
+        for (url, authorization, expected) in cases {
+            assert_eq!(
+                status_for(url, authorization),
+                expected,
+                "{} {:?}",
+                url,
+                authorization
+            );
+        }
+    }
+}
@@ -1,23 +1,32 @@
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
-use std::sync::mpsc::Receiver;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
+use std::thread::{self, JoinHandle, ThreadId};
+use std::time::{Duration, Instant};
 
+use crate::api_server;
 use crate::audio_stream::AudioStream;
-use crate::clipboard_inserter;
+use crate::caret_context;
 use crate::config;
+use crate::download_manager::{DownloadEvent, DownloadManager};
 use crate::hotkeys::KeyboardEvent;
+use crate::insertion::Target;
 use crate::keyboard_layout::KeyboardLayoutDetector;
+use crate::model_cache::{CacheSettings, ModelCache, ModelKey};
+use crate::local_models;
+use crate::model_manager;
+use crate::model_registry;
+use crate::prompt_context::DictationContext;
 use crate::transcriber_utils::{
-    ensure_transcriber_for, select_model_file, transcribe_samples_with, translate_samples_with,
+    ensure_engine_for, ensure_transcriber_for, resolve_model_file, run_pipeline,
+    select_model_file_for_power, transcription_slot, PipelineOptions,
 };
-use crate::whisper::WhisperTranscriber;
 
 #[cfg(feature = "tray-icon")]
 use crate::tray_ui::{tray_post_view, AppView, TrayStatus};
 use crate::tray_ui::{ModelProgress, UiIntent};
+use crate::wyoming_server;
 
 static PRIMING: AtomicBool = AtomicBool::new(false);
 
@@ -25,6 +34,7 @@ static PRIMING: AtomicBool = AtomicBool::new(false);
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum AppStatus {
     Priming,
+    Loading,
     Ready,
     Recording,
     Processing,
@@ -35,6 +45,7 @@ impl AppStatus {
     fn to_tray(self) -> TrayStatus {
         match self {
             AppStatus::Priming => TrayStatus::Priming,
+            AppStatus::Loading => TrayStatus::Loading,
             AppStatus::Ready => TrayStatus::Ready,
             AppStatus::Recording => TrayStatus::Recording,
             AppStatus::Processing => TrayStatus::Processing,
@@ -42,18 +53,29 @@ impl AppStatus {
     }
 }
 
+// Events sent from background workers (downloads, model loads) back to the app thread
+enum AppEvent {
+    Download(DownloadEvent),
+    /// A background model load finished; the id of its thread
+    ModelLoaded(ThreadId),
+}
+
 // Centralized app state for the event loop
 struct AppState {
     status: AppStatus,
     current_language: String,
     active_model: String,
     loading: HashMap<String, ModelProgress>,
-    english_transcriber: Arc<Mutex<Option<WhisperTranscriber>>>,
-    multilingual_transcriber: Arc<Mutex<Option<WhisperTranscriber>>>,
+    download_errors: HashMap<String, String>,
+    downloads: Arc<DownloadManager>,
+    model_cache: Arc<ModelCache>,
     recorded_samples: Arc<Mutex<Vec<f32>>>,
     stream: AudioStream,
     translate_enabled: bool,
     current_device: String,
+    model_loaders: Vec<JoinHandle<()>>,
+    events_tx: Sender<AppEvent>,
+    events_rx: Receiver<AppEvent>,
 }
 
 fn detect_language_code() -> String {
@@ -66,6 +88,19 @@ fn detect_language_code() -> String {
     }
 }
 
+// Determine the language for the next recording based on user preference in config
+fn resolve_language_code() -> String {
+    let pref = config::get_language_preference();
+    if pref == "default" {
+        let code = detect_language_code();
+        println!("Detected language code: {}", code);
+        code
+    } else {
+        println!("Using language from preferences: {}", pref);
+        pref
+    }
+}
+
 pub struct App {
     state: AppState,
 }
@@ -76,21 +111,30 @@ impl App {
         recorded_samples: Arc<Mutex<Vec<f32>>>,
         initial_model: String,
     ) -> Self {
-        let english_transcriber: Arc<Mutex<Option<WhisperTranscriber>>> = Arc::new(Mutex::new(None));
-        let multilingual_transcriber: Arc<Mutex<Option<WhisperTranscriber>>> =
-            Arc::new(Mutex::new(None));
+        let model_cache = Arc::new(ModelCache::new(CacheSettings::from_config()));
+        let (events_tx, events_rx) = channel::<AppEvent>();
+        let downloads = {
+            let events_tx = Mutex::new(events_tx.clone());
+            Arc::new(DownloadManager::new(Box::new(move |event| {
+                let _ = events_tx.lock().unwrap().send(AppEvent::Download(event));
+            })))
+        };
         Self {
             state: AppState {
                 status: AppStatus::Ready, // will be adjusted below
                 current_language: String::from("en"),
                 active_model: initial_model.clone(),
                 loading: HashMap::new(),
-                english_transcriber,
-                multilingual_transcriber,
+                download_errors: HashMap::new(),
+                downloads,
+                model_cache,
                 recorded_samples,
                 stream,
                 translate_enabled: config::get_translate_enabled(),
                 current_device: config::get_device(),
+                model_loaders: Vec::new(),
+                events_tx,
+                events_rx,
             },
         }
         .with_startup_status()
@@ -98,14 +142,11 @@ impl App {
 
     // Adjust initial status to Priming if the selected model (both en/multi) is missing
     fn with_startup_status(mut self) -> Self {
-        let (en_file, multi_file) = get_both_model_filenames(&self.state.active_model);
-        let need_en = if self.state.active_model != "large" {
-            config::get_model_path(&en_file).is_none()
-        } else {
-            false
-        };
+        let (en_file, multi_file) = local_models::model_files(&self.state.active_model);
+        let need_en = en_file != multi_file && config::get_model_path(&en_file).is_none();
         let need_multi = config::get_model_path(&multi_file).is_none();
-        let is_priming = need_en || need_multi;
+        // Missing custom model files can't be downloaded, so there is nothing to prime
+        let is_priming = (need_en || need_multi) && !local_models::is_custom(&self.state.active_model);
         if is_priming {
             self.state.status = AppStatus::Priming;
             PRIMING.store(true, Ordering::SeqCst);
@@ -122,14 +163,46 @@ impl App {
             active_model: self.state.active_model.clone(),
             status: self.state.status.to_tray(),
             loading: self.state.loading.clone(),
+            errors: self.state.download_errors.clone(),
+            fallback_model: self.fallback_in_use(),
             translate_enabled: self.state.translate_enabled,
         };
         tray_post_view(view);
     }
 
+    // Model used instead of the active one while its files are still missing (e.g. downloading)
+    #[cfg(feature = "tray-icon")]
+    fn fallback_in_use(&self) -> Option<String> {
+        let is_english = self.state.current_language.starts_with("en");
+        let model_file = select_model_file_for_power(&self.state.active_model, is_english);
+        let resolved = resolve_model_file(&model_file, is_english);
+        (resolved != model_file).then(|| {
+            model_registry::find_by_file(&resolved)
+                .map(|m| m.id.to_string())
+                .unwrap_or(resolved)
+        })
+    }
+
     fn start_recording(&mut self) {
-        // Guard with status (single-source-of-truth for app logic/UI)
-        if self.state.status != AppStatus::Ready {
+        // Guard with status (single-source-of-truth for app logic/UI).
+        // Recording is allowed while a model loads in the background; transcription waits for it.
+        // While the selected model downloads (priming), the best model already on disk is used.
+        if !matches!(
+            self.state.status,
+            AppStatus::Ready | AppStatus::Loading | AppStatus::Priming
+        ) {
+            return;
+        }
+
+        // Determine language for this recording based on user preference in config
+        let language_code = resolve_language_code();
+        let is_english = language_code.starts_with("en");
+        let selected_file = select_model_file_for_power(&self.state.active_model, is_english);
+        if self.needs_download(&selected_file, is_english) {
+            println!(
+                "No model for '{}' is on disk yet; recording is available once the download finishes",
+                language_code
+            );
             return;
         }
 
@@ -137,18 +210,7 @@ impl App {
         self.state.status = AppStatus::Recording;
         #[cfg(feature = "tray-icon")]
         self.post_view();
-
-        // Determine language for this recording based on user preference in config
-        let pref = config::get_language_preference();
-        let language_code = if pref == "default" {
-            let code = detect_language_code();
-            println!("Detected language code: {}", code);
-            code
-        } else {
-            println!("Using language from preferences: {}", pref);
-            pref
-        };
-        self.state.current_language = language_code.clone();
+        self.state.current_language = language_code;
 
         // Clear previous recording
         {
@@ -163,42 +225,33 @@ impl App {
             .expect("Failed to start the stream");
         self.state.stream.start_capture();
 
-        // Initialize Whisper after starting recording
-        let is_english = language_code.starts_with("en");
-
-        // If compute device preference changed since last time, drop cached transcribers
+        // Contexts are cached per device, so a device change is picked up by the load check below
         let device_now = config::get_device();
         if device_now != self.state.current_device {
             println!(
-                "Compute device changed from '{}' to '{}'; reinitializing whisper contexts",
+                "Compute device changed from '{}' to '{}'; loading whisper contexts for it",
                 self.state.current_device, device_now
             );
-            if let Ok(mut en) = self.state.english_transcriber.lock() {
-                *en = None;
-            }
-            if let Ok(mut ml) = self.state.multilingual_transcriber.lock() {
-                *ml = None;
-            }
             self.state.current_device = device_now;
         }
 
-        // Resolve the model file based on selected model and language
-        let model_file = select_model_file(&self.state.active_model, is_english);
-
-        // Ensure the appropriate transcriber is initialized (with fallback)
-        ensure_transcriber_for(
-            is_english,
-            &model_file,
-            &self.state.english_transcriber,
-            &self.state.multilingual_transcriber,
-        );
+        // Make sure the transcriber for this language (or its fallback while the selected
+        // model downloads) is loaded or being loaded, without blocking
+        let model_file = resolve_model_file(&selected_file, is_english);
+        if !self
+            .state
+            .model_cache
+            .contains(&ModelKey::for_current_device(&model_file))
+        {
+            self.load_models_async(is_english);
+        }
     }
 
     fn stop_and_transcribe(&mut self) {
         // Only process "stop/transcribe" if we were recording
         if self.state.status != AppStatus::Recording {
             println!("No audio recorded");
-            self.state.status = AppStatus::Ready;
+            self.state.status = self.idle_status();
             #[cfg(feature = "tray-icon")]
             self.post_view();
             return;
@@ -231,29 +284,41 @@ impl App {
             );
             let is_english = self.state.current_language.starts_with("en");
 
-            let transcriber = if is_english {
-                &self.state.english_transcriber
-            } else {
-                &self.state.multilingual_transcriber
-            };
-
-            let result = if self.state.translate_enabled {
-                translate_samples_with(
-                    transcriber,
-                    &samples,
-                    self.state.stream.get_sample_rate(),
-                    self.state.stream.get_channels(),
-                    &self.state.current_language,
-                )
-            } else {
-                transcribe_samples_with(
-                    transcriber,
-                    &samples,
-                    self.state.stream.get_sample_rate(),
-                    self.state.stream.get_channels(),
-                    &self.state.current_language,
-                )
-            };
+            // Transcription needs the model: wait for any background load to finish,
+            // then make sure the transcriber exists (covers failed or skipped loads).
+            self.wait_for_model_loads();
+            let model_file = select_model_file_for_power(&self.state.active_model, is_english);
+            if self.needs_download(&model_file, is_english) {
+                eprintln!("No model is on disk for this recording yet; try again once the download finishes");
+                self.state.status = self.idle_status();
+                #[cfg(feature = "tray-icon")]
+                self.post_view();
+                return;
+            }
+            let sample_rate = self.state.stream.get_sample_rate();
+            let channels = self.state.stream.get_channels();
+            let language = self.state.current_language.clone();
+            let translate = self.state.translate_enabled;
+            let context = DictationContext::capture();
+            let target = Target::focused();
+            // Queued behind requests to the local API, which share the loaded models
+            let slot = transcription_slot();
+            let result = ensure_engine_for(is_english, &model_file, &self.state.model_cache)
+                .and_then(|transcriber| {
+                    run_pipeline(
+                        &*transcriber,
+                        &samples,
+                        sample_rate,
+                        channels,
+                        &language,
+                        &PipelineOptions {
+                            translate,
+                            prompt: context.prompt.as_deref(),
+                            profile: target.profile.as_deref(),
+                        },
+                    )
+                });
+            drop(slot);
 
             match result {
                 Ok(transcript) => {
@@ -265,46 +330,147 @@ impl App {
 
                     // Insert the transcript at the current cursor position in a separate thread to avoid blocking
                     std::thread::spawn(move || {
-                        clipboard_inserter::insert_text(&transcript);
-                        println!("Transcript inserted");
+                        let transcript = caret_context::fit_to_caret(&transcript);
+                        match target.insert(&transcript) {
+                            Ok(()) => {
+                                context.remember(&transcript);
+                                println!("Transcript inserted");
+                            }
+                            Err(e) => eprintln!("{}", e),
+                        }
                     });
                 }
                 Err(e) => {
                     eprintln!("{}", e);
                 }
             }
+            // Loaded models stay in the model cache; it unloads them by budget and idle timeout.
+        }
 
-                // After processing, free resources. When using CUDA/GPU, also reset the device to reclaim VRAM.
-                #[cfg(feature = "cuda")]
-                {
-                    if config::use_gpu() {
-                        // Drop only the one we used
-                        crate::transcriber_utils::cleanup_transcriber(transcriber);
-                        // Force CUDA device reset to release remaining allocations/caches
-                        WhisperTranscriber::free_cuda_vram();
-                    }
+        // Back to ready (or loading, if a background load started meanwhile)
+        self.state.status = self.idle_status();
+        #[cfg(feature = "tray-icon")]
+        self.post_view();
+    }
+
+    // Status to return to when not recording/processing
+    fn idle_status(&self) -> AppStatus {
+        if PRIMING.load(Ordering::SeqCst) {
+            AppStatus::Priming
+        } else if !self.state.model_loaders.is_empty() {
+            AppStatus::Loading
+        } else {
+            AppStatus::Ready
+        }
+    }
+
+    /// Load the transcriber for the active model into the model cache in a background thread.
+    fn load_models_async(&mut self, is_english: bool) {
+        // With a remote engine the local model is only loaded when falling back to it
+        if config::get_remote_engine().is_some() {
+            return;
+        }
+        let model_file = select_model_file_for_power(&self.state.active_model, is_english);
+        if self.needs_download(&model_file, is_english) {
+            return;
+        }
+        let model_cache = self.state.model_cache.clone();
+        let events_tx = self.state.events_tx.clone();
+
+        let handle = thread::spawn(move || {
+            if let Err(e) = ensure_transcriber_for(is_english, &model_file, &model_cache) {
+                eprintln!("{}", e);
+            }
+            let _ = events_tx.send(AppEvent::ModelLoaded(thread::current().id()));
+        });
+        self.state.model_loaders.push(handle);
+
+        if self.state.status == AppStatus::Ready {
+            self.state.status = AppStatus::Loading;
+            #[cfg(feature = "tray-icon")]
+            self.post_view();
+        }
+    }
+
+    // While the selected model downloads, dictation only uses models already on disk:
+    // anything else would start a second download and block transcription until it ends
+    // (a remote engine without local fallback needs no model at all)
+    fn needs_download(&self, model_file: &str, is_english: bool) -> bool {
+        let remote_only = config::get_remote_engine().is_some_and(|r| !r.fallback_to_local);
+        !remote_only
+            && self.state.loading.contains_key(&self.state.active_model)
+            && config::get_model_path(&resolve_model_file(model_file, is_english)).is_none()
+    }
+
+    // Block until all background model loads have finished
+    fn wait_for_model_loads(&mut self) {
+        if self.state.model_loaders.is_empty() {
+            return;
+        }
+        println!("Waiting for model to finish loading");
+        for handle in self.state.model_loaders.drain(..) {
+            let _ = handle.join();
+        }
+    }
+
+    fn handle_app_event(&mut self, event: AppEvent) {
+        match event {
+            AppEvent::Download(DownloadEvent::Progress {
+                model,
+                percent,
+                eta_secs,
+            }) => {
+                self.state
+                    .loading
+                    .insert(model, ModelProgress { percent, eta_secs });
+                #[cfg(feature = "tray-icon")]
+                self.post_view();
+            }
+            AppEvent::Download(DownloadEvent::Finished { model }) => {
+                println!("Model '{}' downloaded", model);
+                self.finish_download(&model);
+                if model == self.state.active_model {
+                    let is_english = resolve_language_code().starts_with("en");
+                    self.load_models_async(is_english);
                 }
-                #[cfg(not(feature = "cuda"))]
-                {
-                    // On CPU builds, just drop the transcriber if you also want to reclaim RAM
-                    // (optional; no-op if you prefer to keep it cached for performance).
-                    // crate::transcriber_utils::cleanup_transcriber(transcriber);
+                #[cfg(feature = "tray-icon")]
+                self.post_view();
+            }
+            AppEvent::Download(DownloadEvent::Failed { model, error }) => {
+                self.finish_download(&model);
+                self.state.download_errors.insert(model, error);
+                #[cfg(feature = "tray-icon")]
+                self.post_view();
+            }
+            AppEvent::Download(DownloadEvent::Cancelled { model }) => {
+                self.finish_download(&model);
+                #[cfg(feature = "tray-icon")]
+                self.post_view();
+            }
+            AppEvent::ModelLoaded(id) => {
+                // The loader may not have returned yet; joining only waits for it to exit
+                let loaders = &mut self.state.model_loaders;
+                if let Some(i) = loaders.iter().position(|h| h.thread().id() == id) {
+                    let _ = loaders.remove(i).join();
                 }
-
-            // After processing, free GPU VRAM by dropping the transcriber (when using CUDA/GPU).
-            #[cfg(feature = "cuda")]
-            {
-                if config::use_gpu() {
-                    // Drop only the one we used
-                    crate::transcriber_utils::cleanup_transcriber(transcriber);
+                if self.state.status == AppStatus::Loading && self.state.model_loaders.is_empty() {
+                    self.state.status = AppStatus::Ready;
+                    #[cfg(feature = "tray-icon")]
+                    self.post_view();
                 }
             }
         }
+    }
 
-        // Back to ready
-        self.state.status = AppStatus::Ready;
-        #[cfg(feature = "tray-icon")]
-        self.post_view();
+    // A download ended one way or another: drop its progress and leave priming
+    fn finish_download(&mut self, model: &str) {
+        self.state.loading.remove(model);
+        if self.state.loading.is_empty() {
+            PRIMING.store(false, Ordering::SeqCst);
+            if self.state.status == AppStatus::Priming {
+                self.state.status = self.idle_status();
+            }
+        }
     }
 
     pub fn run_loop(
@@ -312,14 +478,20 @@ impl App {
         kb_receiver: Receiver<KeyboardEvent>,
         ui_receiver: Receiver<UiIntent>,
     ) -> ! {
-        // Kick off initial ensure if we are priming
-        if PRIMING.load(Ordering::SeqCst) {
-            let model = self.state.active_model.clone();
-            self.ensure_model_async(model);
+        // Serve the same models to other local tools and voice pipelines, if enabled
+        api_server::start_if_enabled(self.state.model_cache.clone());
+        wyoming_server::start_if_enabled(self.state.model_cache.clone());
+
+        // Kick off initial ensure if we are priming, otherwise preload the model in the background
+        let model = self.state.active_model.clone();
+        if self.ensure_model_async(model) {
+            let is_english = resolve_language_code().starts_with("en");
+            self.load_models_async(is_english);
         }
         #[cfg(feature = "tray-icon")]
         self.post_view();
 
+        let mut last_cache_check = Instant::now();
         loop {
             // Handle UI intents (model selection, quit)
             if let Ok(intent) = ui_receiver.try_recv() {
@@ -337,8 +509,46 @@ impl App {
                             #[cfg(feature = "tray-icon")]
                             self.post_view();
 
-                            // Ensure model is available (downloads if needed) and update progress map
-                            self.ensure_model_async(model);
+                            // Ensure model is available (downloads if needed) and update progress map;
+                            // if it is already on disk, load it in the background right away
+                            if self.ensure_model_async(model) {
+                                let is_english = resolve_language_code().starts_with("en");
+                                self.load_models_async(is_english);
+                            }
+                        }
+                    }
+                    UiIntent::SelectDevice(device) => {
+                        if let Err(e) = config::save_device(&device) {
+                            eprintln!("Failed to save compute device: {}", e);
+                        }
+                        let device_now = config::get_device();
+                        if device_now != self.state.current_device {
+                            println!(
+                                "Compute device changed from '{}' to '{}'; reloading whisper contexts",
+                                self.state.current_device, device_now
+                            );
+                            self.state.current_device = device_now;
+                            // Contexts on the previous device are no longer used
+                            self.state.model_cache.clear();
+                            let is_english = resolve_language_code().starts_with("en");
+                            self.load_models_async(is_english);
+                        }
+                    }
+                    UiIntent::RedownloadModel(model) => {
+                        match model_registry::find(&model) {
+                            Some(info) => match model_manager::discard_model_files(&model) {
+                                Ok(()) => {
+                                    println!("Downloading model '{}' again", model);
+                                    self.start_download(model, vec![info.file_name.to_string()]);
+                                }
+                                Err(e) => eprintln!("Failed to remove model '{}': {}", model, e),
+                            },
+                            None => eprintln!("Unknown model '{}'", model),
+                        }
+                    }
+                    UiIntent::CancelDownload(model) => {
+                        if self.state.downloads.cancel(&model) {
+                            println!("Cancelling download of model '{}'", model);
                         }
                     }
                     UiIntent::ToggleTranslate(enabled) => {
@@ -360,6 +570,11 @@ impl App {
                 }
             }
 
+            // Handle background worker events (downloads finished, models loaded)
+            if let Ok(event) = self.state.events_rx.try_recv() {
+                self.handle_app_event(event);
+            }
+
             // Check for keyboard events
             if let Ok(event) = kb_receiver.try_recv() {
                 match event {
@@ -379,124 +594,66 @@ impl App {
                 }
             }
 
+            // Pick up changed memory budgets and idle timeout, and unload idle models, once in a while
+            if last_cache_check.elapsed() >= Duration::from_secs(5) {
+                self.state
+                    .model_cache
+                    .update_settings(CacheSettings::from_config());
+                last_cache_check = Instant::now();
+            }
+
             // Sleep to reduce CPU usage
             thread::sleep(Duration::from_millis(10));
         }
     }
 
-    fn ensure_model_async(&mut self, model: String) {
+    /// Start downloading any missing files of `model` in the background.
+    /// Returns true when the model is already available locally.
+    fn ensure_model_async(&mut self, model: String) -> bool {
         // quick existence check
-        let (en_model_file, multi_model_file) = get_both_model_filenames(&model);
-        let en_exists = if model != "large" {
-            config::get_model_path(&en_model_file).is_some()
-        } else {
-            true
-        };
+        let (en_model_file, multi_model_file) = local_models::model_files(&model);
+        let en_exists =
+            en_model_file == multi_model_file || config::get_model_path(&en_model_file).is_some();
         let multi_exists = config::get_model_path(&multi_model_file).is_some();
 
         if en_exists && multi_exists {
-            return;
+            return true;
         }
 
-        // Mark as loading at 0%
-        #[cfg(feature = "tray-icon")]
-        {
-            self.state.loading.insert(
-                model.clone(),
-                ModelProgress {
-                    percent: 0,
-                    eta_secs: 0,
-                },
-            );
-            self.post_view();
+        // User-supplied models can't be downloaded; loading reports the missing file and falls back
+        if local_models::is_custom(&model) {
+            eprintln!("Custom model '{}' file is missing", model);
+            return true;
         }
 
-        // Spawn download worker
-        let ui_model = model.clone();
-        thread::spawn(move || {
-            // forward progress to app thread via global callback by updating config or external event mechanism
-            // Here, we reuse whisper's callback; the app thread will not receive direct calls,
-            // so we emit updates via a simple side-channel approach: we cannot mutate self here,
-            // thus we only drive downloads; the app thread will refresh progress via callback closures set below.
-            let _ = ui_model; // kept to satisfy move
-        });
-
-        // Register progress callback that updates our local state and posts snapshots
-        let last_p = Arc::new(AtomicU8::new(255)); // force first emit
-        let model_for_cb = model.clone();
-        let last_p_cb = last_p.clone();
-        WhisperTranscriber::set_download_progress_callback(Some(Box::new({
-            let model_for_cb_clone = model_for_cb.clone();
-            move |percent, eta_secs| {
-                let p = percent.clamp(0.0, 100.0) as u8;
-                let prev = last_p_cb.swap(p, Ordering::SeqCst);
-                if p == prev {
-                    return;
-                }
-                #[cfg(feature = "tray-icon")]
-                {
-                    // This closure runs on a background thread; only send snapshots to the UI.
-                    let mut loading = HashMap::new();
-                    loading.insert(
-                        model_for_cb_clone.clone(),
-                        ModelProgress {
-                            percent: p,
-                            eta_secs,
-                        },
-                    );
-                    let view = AppView {
-                        active_model: model_for_cb_clone.clone(),
-                        status: if PRIMING.load(Ordering::SeqCst) {
-                            TrayStatus::Priming
-                        } else {
-                            TrayStatus::Ready
-                        },
-                        loading,
-                        translate_enabled: config::get_translate_enabled(),
-                    };
-                    tray_post_view(view);
-                }
-            }
-        })));
-
-        // Perform the downloads synchronously on a worker thread so the app loop remains responsive
-        let need_en = !en_exists && model != "large";
-        let need_multi = !multi_exists;
-        let model_for_done = model.clone();
-        thread::spawn(move || {
-            if need_en {
-                let _ = WhisperTranscriber::download_model(&en_model_file);
-            }
-            if need_multi {
-                let _ = WhisperTranscriber::download_model(&multi_model_file);
-            }
-            WhisperTranscriber::set_download_progress_callback(None);
-            PRIMING.store(false, Ordering::SeqCst);
-            #[cfg(feature = "tray-icon")]
-            {
-                // Clear progress for the model
-                let view = AppView {
-                    active_model: model_for_done.clone(),
-                    status: TrayStatus::Ready,
-                    loading: HashMap::new(),
-                    translate_enabled: config::get_translate_enabled(),
-                };
-                tray_post_view(view);
-            }
-        });
+        let mut files = Vec::new();
+        if !en_exists {
+            files.push(en_model_file);
+        }
+        if !multi_exists {
+            files.push(multi_model_file);
+        }
+        self.start_download(model, files);
+        false
     }
-}
 
-fn get_both_model_filenames(model: &str) -> (String, String) {
-    match model {
-        "base" | "tiny" | "small" | "medium" => (
-            format!("ggml-{}.en.bin", model),
-            format!("ggml-{}.bin", model),
-        ),
-        "large" => (
-            format!("ggml-{}-v2.bin", model),
-            format!("ggml-{}-v2.bin", model),
-        ),
-        _ => ("ggml-base.en.bin".into(), "ggml-base.bin".into()),
+    // Hand files of `model` to the download manager and show its progress
+    fn start_download(&mut self, model: String, files: Vec<String>) {
+        // Mark as loading at 0% and clear the error of a previous attempt
+        self.state.download_errors.remove(&model);
+        self.state.loading.insert(
+            model.clone(),
+            ModelProgress {
+                percent: 0,
+                eta_secs: 0,
+            },
+        );
+        #[cfg(feature = "tray-icon")]
+        self.post_view();
+
+        // Files are fetched by the download manager; it reports back through AppEvent::Download
+        if !self.state.downloads.start(&model, files) {
+            println!("Model '{}' is already being downloaded", model);
+        }
     }
 }
@@ -0,0 +1,100 @@
+use std::io::Cursor;
+
+/// Sample rate whisper models expect
+pub const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Convert captured audio to 16 kHz mono, the input format of whisper
+pub fn to_whisper_input(samples: &[f32], sample_rate: u32, channels: u16) -> Vec<f32> {
+    let mono_samples = if channels > 1 {
+        convert_to_mono(samples, channels as usize)
+    } else {
+        samples.to_vec()
+    };
+    if sample_rate != WHISPER_SAMPLE_RATE {
+        resample(&mono_samples, sample_rate, WHISPER_SAMPLE_RATE)
+    } else {
+        mono_samples
+    }
+}
+
+/// Convert multi-channel audio to mono by averaging channels
+pub fn convert_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
+    let mono_len = samples.len() / channels;
+    let mut mono_samples = Vec::with_capacity(mono_len);
+
+    for i in 0..mono_len {
+        let mut sum = 0.0;
+        for c in 0..channels {
+            sum += samples[i * channels + c];
+        }
+        mono_samples.push(sum / channels as f32);
+    }
+
+    mono_samples
+}
+
+/// Simple linear resampling
+pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if samples.is_empty() {
+        return Vec::new();
+    }
+    let ratio = from_rate as f64 / to_rate as f64;
+    let new_len = (samples.len() as f64 / ratio) as usize;
+    let mut resampled = Vec::with_capacity(new_len);
+
+    for i in 0..new_len {
+        let pos = i as f64 * ratio;
+        let pos_floor = pos.floor() as usize;
+        let pos_ceil = (pos_floor + 1).min(samples.len() - 1);
+        let frac = pos - pos_floor as f64;
+
+        let sample = samples[pos_floor] * (1.0 - frac as f32) + samples[pos_ceil] * frac as f32;
+        resampled.push(sample);
+    }
+
+    resampled
+}
+
+/// Encode mono samples as a 16-bit PCM WAV file in memory
+pub fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut buffer = Cursor::new(Vec::new());
+    {
+        let mut writer = hound::WavWriter::new(&mut buffer, spec)
+            .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
+        for &sample in samples {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            writer
+                .write_sample(value)
+                .map_err(|e| format!("Failed to write WAV sample: {}", e))?;
+        }
+        writer
+            .finalize()
+            .map_err(|e| format!("Failed to finalize WAV data: {}", e))?;
+    }
+    Ok(buffer.into_inner())
+}
+
+/// Decode a WAV file into interleaved f32 samples, returning (samples, sample rate, channels)
+pub fn decode_wav(data: &[u8]) -> Result<(Vec<f32>, u32, u16), String> {
+    let reader =
+        hound::WavReader::new(Cursor::new(data)).map_err(|e| format!("Invalid WAV data: {}", e))?;
+    let spec = reader.spec();
+    let samples: Result<Vec<f32>, _> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.into_samples::<f32>().collect(),
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect()
+        }
+    };
+    let samples = samples.map_err(|e| format!("Failed to read WAV samples: {}", e))?;
+    Ok((samples, spec.sample_rate, spec.channels))
+}
@@ -0,0 +1,338 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::audio_utils;
+use crate::compute_policy;
+use crate::config;
+use crate::local_models;
+use crate::model_manager::{self, ModelState};
+use crate::model_registry;
+use crate::whisper::{DecodeOptions, WhisperTranscriber};
+
+const USAGE: &str = "Usage: voice_input bench [options]
+  --models <id,...>     Models to run (default: all downloaded models)
+  --audio <path>        WAV file or directory of WAV files (repeatable)
+  --device <cpu,gpu>    Devices to run on (default: configured device)
+  --beam <n,...>        Beam sizes, 1 = greedy (default: 5)
+  --threads <n,...>     CPU thread counts (default: one per CPU)
+  --language <code>     Language of the fixtures (default: en)
+  --runs <n>            Decode each fixture n times and keep the fastest (default: 1)
+  --json                Print results as JSON instead of a table
+  --output <file>       Write the report to a file instead of stdout";
+
+/// One row of the report
+#[derive(Debug, Serialize)]
+struct BenchResult {
+    model: String,
+    device: String,
+    beam_size: i32,
+    threads: i32,
+    audio_secs: f64,
+    load_secs: f64,
+    decode_secs: f64,
+    /// Decode time divided by audio duration; below 1.0 is faster than real time
+    real_time_factor: f64,
+    peak_memory_mb: Option<u64>,
+}
+
+struct Fixture {
+    name: String,
+    // 16 kHz mono
+    samples: Vec<f32>,
+}
+
+struct BenchOptions {
+    models: Vec<String>,
+    audio: Vec<PathBuf>,
+    devices: Vec<String>,
+    beams: Vec<i32>,
+    threads: Vec<i32>,
+    language: String,
+    runs: u32,
+    json: bool,
+    output: Option<PathBuf>,
+}
+
+fn parse_list<T: std::str::FromStr>(value: &str, flag: &str) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .map(|v| v.parse().map_err(|_| format!("Invalid value '{}' for {}", v, flag)))
+        .collect()
+}
+
+fn parse_options(args: &[&str]) -> Result<BenchOptions, String> {
+    let mut options = BenchOptions {
+        models: Vec::new(),
+        audio: Vec::new(),
+        devices: vec![config::get_device()],
+        beams: vec![DecodeOptions::default().beam_size],
+        threads: vec![compute_policy::available_threads() as i32],
+        language: "en".to_string(),
+        runs: 1,
+        json: false,
+        output: None,
+    };
+
+    let mut iter = args.iter();
+    while let Some(&flag) = iter.next() {
+        if flag == "--json" {
+            options.json = true;
+            continue;
+        }
+        if flag == "--help" || flag == "-h" {
+            return Err(USAGE.to_string());
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("Missing value for {}\n\n{}", flag, USAGE))?;
+        match flag {
+            "--models" => options.models = parse_list(value, flag)?,
+            "--audio" => options.audio.push(PathBuf::from(value)),
+            "--device" => options.devices = parse_list(value, flag)?,
+            "--beam" => options.beams = parse_list(value, flag)?,
+            "--threads" => options.threads = parse_list(value, flag)?,
+            "--language" => options.language = value.to_string(),
+            "--runs" => options.runs = value.parse().map_err(|_| "Invalid --runs".to_string())?,
+            "--output" => options.output = Some(PathBuf::from(value)),
+            _ => return Err(format!("Unknown option {}\n\n{}", flag, USAGE)),
+        }
+    }
+    if options.devices.iter().any(|d| d != "cpu" && d != "gpu") {
+        return Err("--device accepts cpu and gpu".to_string());
+    }
+    Ok(options)
+}
+
+/// WAV files given on the command line (files or directories)
+pub fn collect_wav_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let entries = fs::read_dir(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let mut wavs: Vec<PathBuf> = entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wav")))
+                .collect();
+            wavs.sort();
+            files.extend(wavs);
+        } else if path.exists() {
+            files.push(path.clone());
+        } else {
+            return Err(format!("Audio file not found: {}", path.display()));
+        }
+    }
+    Ok(files)
+}
+
+/// Read a WAV file and convert it to 16 kHz mono
+pub fn load_wav_16k(path: &Path) -> Result<Vec<f32>, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let (samples, sample_rate, channels) = audio_utils::decode_wav(&data)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(audio_utils::to_whisper_input(&samples, sample_rate, channels))
+}
+
+// Ten seconds of a voice-like signal (harmonics with a syllable-rate envelope).
+// Decode time on real speech is more representative; this only keeps `bench` usable out of the box.
+fn synthetic_fixture() -> Fixture {
+    let rate = audio_utils::WHISPER_SAMPLE_RATE as f32;
+    let samples = (0..(10 * audio_utils::WHISPER_SAMPLE_RATE))
+        .map(|i| {
+            let t = i as f32 / rate;
+            let envelope = (std::f32::consts::PI * 4.0 * t).sin().abs();
+            let pitch = 140.0 + 30.0 * (std::f32::consts::PI * 0.5 * t).sin();
+            let voice: f32 = (1..=5)
+                .map(|h| (2.0 * std::f32::consts::PI * pitch * h as f32 * t).sin() / h as f32)
+                .sum();
+            0.2 * envelope * voice
+        })
+        .collect();
+    Fixture {
+        name: "synthetic-10s".to_string(),
+        samples,
+    }
+}
+
+fn load_fixtures(paths: &[PathBuf]) -> Result<Vec<Fixture>, String> {
+    let mut paths = paths.to_vec();
+    if paths.is_empty() {
+        // Fixtures bundled with the source tree or placed in the data directory
+        let defaults = [
+            config::get_data_dir().map(|d| d.join("bench")),
+            Some(PathBuf::from("assets/bench")),
+        ];
+        paths = defaults.into_iter().flatten().filter(|p| p.is_dir()).collect();
+    }
+
+    let files = collect_wav_files(&paths)?;
+    if files.is_empty() {
+        println!("No WAV fixtures found; using a synthetic 10 s signal (pass --audio for real speech)");
+        return Ok(vec![synthetic_fixture()]);
+    }
+    files
+        .iter()
+        .map(|path| {
+            Ok(Fixture {
+                name: path.display().to_string(),
+                samples: load_wav_16k(path)?,
+            })
+        })
+        .collect()
+}
+
+// Model id/name -> model file, for registry models and custom models
+fn model_file(model: &str) -> String {
+    match model_registry::find(model) {
+        Some(info) => info.file_name.to_string(),
+        None => local_models::model_files(model).1,
+    }
+}
+
+// Start measuring peak memory from now on (Linux: reset VmHWM via clear_refs)
+fn reset_peak_memory() {
+    let _ = fs::write("/proc/self/clear_refs", "5");
+}
+
+// Peak resident memory of the process in MB since the last reset
+fn peak_memory_mb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb / 1024)
+}
+
+fn print_table(results: &[BenchResult]) -> String {
+    let mut out = format!(
+        "{:<22} {:<6} {:>4} {:>7} {:>9} {:>9} {:>10} {:>7} {:>9}\n",
+        "MODEL", "DEVICE", "BEAM", "THREADS", "AUDIO s", "LOAD s", "DECODE s", "RTF", "PEAK MB"
+    );
+    for r in results {
+        out.push_str(&format!(
+            "{:<22} {:<6} {:>4} {:>7} {:>9.1} {:>9.2} {:>10.2} {:>7.3} {:>9}\n",
+            r.model,
+            r.device,
+            r.beam_size,
+            r.threads,
+            r.audio_secs,
+            r.load_secs,
+            r.decode_secs,
+            r.real_time_factor,
+            r.peak_memory_mb
+                .map(|m| m.to_string())
+                .unwrap_or_else(|| "-".to_string())
+        ));
+    }
+    out
+}
+
+/// `voice_input bench`: measure load time, decode time, real-time factor and peak memory
+pub fn run(args: &[&str]) -> Result<(), String> {
+    let options = parse_options(args)?;
+
+    let models = if options.models.is_empty() {
+        model_manager::list_models()
+            .into_iter()
+            .filter(|m| m.state == ModelState::Present)
+            .map(|m| m.info.id.to_string())
+            .collect()
+    } else {
+        options.models.clone()
+    };
+    if models.is_empty() {
+        return Err("No downloaded models to benchmark; see `voice_input models list`".to_string());
+    }
+
+    let fixtures = load_fixtures(&options.audio)?;
+    let audio_secs: f64 = fixtures
+        .iter()
+        .map(|f| f.samples.len() as f64 / audio_utils::WHISPER_SAMPLE_RATE as f64)
+        .sum();
+    println!(
+        "Benchmarking {} model(s) on {} fixture(s), {:.1} s of audio",
+        models.len(),
+        fixtures.len(),
+        audio_secs
+    );
+
+    let mut results = Vec::new();
+    for model in &models {
+        for device in &options.devices {
+            let use_gpu = device == "gpu";
+            if use_gpu && !cfg!(feature = "cuda") {
+                eprintln!("Skipping gpu: this build has no CUDA support");
+                continue;
+            }
+
+            reset_peak_memory();
+            let start = Instant::now();
+            let transcriber = match WhisperTranscriber::new_on_device(&model_file(model), use_gpu) {
+                Ok(t) => t,
+                Err(e) => {
+                    eprintln!("Skipping {} on {}: {}", model, device, e);
+                    continue;
+                }
+            };
+            let load_secs = start.elapsed().as_secs_f64();
+
+            for &beam_size in &options.beams {
+                for &threads in &options.threads {
+                    let decode = DecodeOptions {
+                        beam_size,
+                        threads: Some(threads),
+                        low_priority: false,
+                        initial_prompt: None,
+                    };
+                    reset_peak_memory();
+                    let mut decode_secs = 0.0;
+                    for fixture in &fixtures {
+                        let mut best = f64::MAX;
+                        for _ in 0..options.runs.max(1) {
+                            let start = Instant::now();
+                            transcriber
+                                .decode(&fixture.samples, Some(&options.language), false, &decode)
+                                .map_err(|e| format!("{} on {}: {}", model, fixture.name, e))?;
+                            best = best.min(start.elapsed().as_secs_f64());
+                        }
+                        decode_secs += best;
+                    }
+                    let result = BenchResult {
+                        model: model.clone(),
+                        device: device.clone(),
+                        beam_size,
+                        threads,
+                        audio_secs,
+                        load_secs,
+                        decode_secs,
+                        real_time_factor: decode_secs / audio_secs.max(f64::EPSILON),
+                        peak_memory_mb: peak_memory_mb(),
+                    };
+                    println!(
+                        "{} on {} (beam {}, {} threads): RTF {:.3}",
+                        model, device, beam_size, threads, result.real_time_factor
+                    );
+                    results.push(result);
+                }
+            }
+        }
+    }
+
+    let report = if options.json {
+        serde_json::to_string_pretty(&results).map_err(|e| e.to_string())?
+    } else {
+        print_table(&results)
+    };
+    match &options.output {
+        Some(path) => {
+            fs::write(path, &report)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            println!("Report written to {}", path.display());
+        }
+        None => println!("\n{}", report),
+    }
+    Ok(())
+}
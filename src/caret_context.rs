@@ -0,0 +1,197 @@
+use crate::accessibility;
+use crate::config;
+use crate::post_processing::is_acronym_start;
+
+// Characters read before the caret; enough to see the end of the previous sentence
+const CONTEXT_CHARS: usize = 80;
+
+/// Adapt `text` to the text before the caret in the focused widget: capitalize at sentence
+/// starts, lowercase and drop the final period mid-sentence, add a separating space.
+/// Unchanged when disabled or AT-SPI can't tell what is before the caret.
+pub fn fit_to_caret(text: &str) -> String {
+    if !config::get_post_processing().caret_context {
+        return text.to_string();
+    }
+    match accessibility::text_before_caret(CONTEXT_CHARS) {
+        Some(before) => fit_to_context(text, &before),
+        None => text.to_string(),
+    }
+}
+
+// `text` as inserted right after `before`
+fn fit_to_context(text: &str, before: &str) -> String {
+    // Keep the trailing space (or newline) from post-processing out of the way
+    let body = text.trim_end();
+    let tail = &text[body.len()..];
+    if body.is_empty() {
+        return text.to_string();
+    }
+
+    let previous = before.trim_end_matches([' ', '\t']);
+    let Some(last) = previous.chars().last().filter(|c| *c != '\n') else {
+        // Start of the document or of a line: as whisper wrote it
+        return text.to_string();
+    };
+
+    let mut body = body.to_string();
+    if matches!(last, '.' | '!' | '?' | '…') {
+        body = set_first_letter(&body, true);
+    } else if !matches!(last, ':') {
+        if is_capitalized_for_segment_start(&body, before) {
+            body = set_first_letter(&body, false);
+        }
+        // A lone final period ends the sentence the caret is in the middle of
+        if body.ends_with('.') && !body.ends_with("..") {
+            body.pop();
+        }
+    }
+
+    let needs_space = previous.len() == before.len()
+        && !matches!(last, '(' | '[' | '{' | '«' | '“' | '"' | '\'' | '/' | '-')
+        && !body.starts_with([',', '.', ';', ':', '!', '?', ')', ']', '»', '”', '…']);
+    if needs_space {
+        body.insert(0, ' ');
+    }
+    body + tail
+}
+
+// Common words whisper only capitalizes because they start the segment
+const SENTENCE_STARTERS: &[&str] = &[
+    "a", "an", "and", "as", "at", "because", "but", "by", "for", "from", "he", "if", "in", "is",
+    "it", "not", "of", "on", "or", "she", "so", "that", "the", "then", "there", "they", "this",
+    "to", "we", "when", "which", "with", "you", "а", "в", "вы", "если", "и", "или", "как",
+    "когда", "мы", "на", "не", "но", "он", "она", "они", "потому", "с", "так", "то", "что",
+    "это",
+];
+
+// Whether the first word of `text` is capitalized only because whisper started a sentence
+// with it: it's lowercase elsewhere or a common word, and never capitalized mid-sentence.
+// Names ("Bob") and anything unknown keep their capital.
+fn is_capitalized_for_segment_start(text: &str, before: &str) -> bool {
+    let Some(pos) = text.find(char::is_alphabetic) else {
+        return false;
+    };
+    let word: String = text[pos..]
+        .chars()
+        .take_while(|c| c.is_alphanumeric())
+        .collect();
+    if is_acronym_start(&text[pos..]) || word.chars().skip(1).any(char::is_uppercase) {
+        return false;
+    }
+    let lower = word.to_lowercase();
+    if lower == word {
+        return false;
+    }
+
+    let rest = &text[pos + word.len()..];
+    let mut seen_lowercase = false;
+    // The start of the context may be cut off mid-sentence; the rest continues the first word
+    for other in mid_sentence_words(before, true).chain(mid_sentence_words(rest, false)) {
+        if other == word {
+            return false;
+        }
+        seen_lowercase |= other == lower;
+    }
+    seen_lowercase || SENTENCE_STARTERS.contains(&lower.as_str())
+}
+
+// Words of `text` that don't start a sentence
+fn mid_sentence_words(text: &str, at_start: bool) -> impl Iterator<Item = &str> {
+    let mut sentence_start = at_start;
+    text.split_whitespace().filter_map(move |token| {
+        let starts = sentence_start;
+        sentence_start = token.ends_with(['.', '!', '?', '…']);
+        let word = token.trim_matches(|c: char| !c.is_alphanumeric());
+        (!starts && !word.is_empty()).then_some(word)
+    })
+}
+
+// Upper- or lowercase the first letter; "I" and acronyms ("NASA") stay as they are
+fn set_first_letter(text: &str, upper: bool) -> String {
+    let Some(pos) = text.find(char::is_alphabetic) else {
+        return text.to_string();
+    };
+    if !upper && is_acronym_start(&text[pos..]) {
+        return text.to_string();
+    }
+    let first = text[pos..].chars().next().unwrap_or_default();
+    let replaced: String = if upper {
+        first.to_uppercase().collect()
+    } else {
+        first.to_lowercase().collect()
+    };
+    format!(
+        "{}{}{}",
+        &text[..pos],
+        replaced,
+        &text[pos + first.len_utf8()..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_to_context() {
+        // (text before the caret, dictated text, inserted text)
+        let cases = [
+            ("", "Hello there. ", "Hello there. "),
+            ("First line\n", "Hello there.", "Hello there."),
+            ("Done.", "the next step.", " The next step."),
+            ("Really?  ", "yes.", "Yes."),
+            ("I think", "The weather is nice.", " the weather is nice"),
+            ("Я думаю", "Это правда.", " это правда"),
+            ("Note:", "Bob called.", " Bob called."),
+            ("(", "Hello", "Hello"),
+            ("We said", ", right?", ", right?"),
+            ("Call", "NASA now.", " NASA now"),
+            ("so", "I agree...", " I agree..."),
+            ("Buy an", "iPhone today.", " iPhone today"),
+        ];
+        for (before, text, expected) in cases {
+            assert_eq!(
+                fit_to_context(text, before),
+                expected,
+                "{:?} + {:?}",
+                before,
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_proper_nouns() {
+        let cases = [
+            ("I told", "Bob about it.", " Bob about it"),
+            ("on", "Monday we start.", " Monday we start"),
+            // Lowercase elsewhere: only capitalized for starting the segment
+            (
+                "I think",
+                "Pretty sure it's pretty good.",
+                " pretty sure it's pretty good",
+            ),
+            // Capitalized mid-sentence elsewhere: a name, even if it's a common word
+            ("we asked", "So if So agrees.", " So if So agrees"),
+            (
+                "so I told Bill",
+                "Bill will pay the bill.",
+                " Bill will pay the bill",
+            ),
+            (
+                "I met",
+                "Anna and later Anna again.",
+                " Anna and later Anna again",
+            ),
+        ];
+        for (before, text, expected) in cases {
+            assert_eq!(
+                fit_to_context(text, before),
+                expected,
+                "{:?} + {:?}",
+                before,
+                text
+            );
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::path::Path;
+
+use crate::bench;
+use crate::config;
+use crate::eval;
+use crate::model_integrity;
+use crate::model_manager::{self, ModelState};
+use crate::model_registry;
+use crate::replacements;
+use crate::whisper::WhisperTranscriber;
+
+const USAGE: &str = "Usage:
+  voice_input                          Run the voice input app
+  voice_input models list              List known models with state, size and last use
+  voice_input models delete <id>       Delete a downloaded model
+  voice_input models download <id>     Download a model (resumes partial downloads)
+  voice_input models redownload <id>   Delete and download a model again
+  voice_input bench [options]          Measure speed and memory per model (see bench --help)
+  voice_input eval <dir> [options]     Word error rate against reference transcripts (see eval --help)
+  voice_input replacements list        Show the replacement dictionary
+  voice_input replacements import <f>  Merge rules from a dictionary file
+  voice_input replacements export <f>  Write the replacement dictionary to a file";
+
+/// Run a command-line subcommand and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["models", "list"] | ["models"] => {
+            list_models();
+            Ok(())
+        }
+        ["models", "delete", id] => model_manager::delete_model(id).map(|freed| {
+            println!("Freed {}", model_manager::format_size(freed));
+        }),
+        ["models", "download", id] => download_model(id),
+        ["models", "redownload", id] => {
+            model_manager::discard_model_files(id).and_then(|_| download_model(id))
+        }
+        ["bench", rest @ ..] => bench::run(rest),
+        ["eval", rest @ ..] => eval::run(rest),
+        ["replacements", "list"] | ["replacements"] => replacements::list(),
+        ["replacements", "import", file] => replacements::import(Path::new(file)),
+        ["replacements", "export", file] => replacements::export(Path::new(file)),
+        ["help"] | ["--help"] | ["-h"] => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("Unknown command: {}\n\n{}", args.join(" "), USAGE)),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+fn list_models() {
+    let models = model_manager::list_models();
+    println!(
+        "{:<22} {:<8} {:>9}  {:<16}  {}",
+        "MODEL", "STATE", "SIZE", "LAST USED", "PATH"
+    );
+    let mut total = 0;
+    for status in &models {
+        let in_use = if model_manager::is_in_use(status.info.id) {
+            " (active)"
+        } else {
+            ""
+        };
+        println!(
+            "{:<22} {:<8} {:>9}  {:<16}  {}{}",
+            status.info.id,
+            status.state.label(),
+            if status.size_on_disk > 0 {
+                model_manager::format_size(status.size_on_disk)
+            } else {
+                "-".to_string()
+            },
+            model_manager::format_last_used(status.last_used),
+            status
+                .path
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+            in_use
+        );
+        if let ModelState::Corrupt(reason) = &status.state {
+            println!("    {}", reason);
+        }
+        total += status.size_on_disk;
+    }
+    println!("\nTotal on disk: {}", model_manager::format_size(total));
+}
+
+fn download_model(id: &str) -> Result<(), String> {
+    let info = model_registry::find(id).ok_or_else(|| format!("Unknown model '{}'", id))?;
+    if let Some(path) = config::get_model_path(info.file_name) {
+        if model_integrity::verify_model_file(&path).is_ok() {
+            println!("Model '{}' is already downloaded: {}", id, path.display());
+            return Ok(());
+        }
+    }
+    WhisperTranscriber::download_model(info.file_name)?;
+    println!("Model '{}' is ready", id);
+    Ok(())
+}
@@ -3,18 +3,23 @@ use rdev::{simulate, EventType, Key};
 use std::thread;
 use std::time::Duration;
 
+use crate::config::Insertion;
+
 /// Inserts text at the current cursor position by using the clipboard
 ///
 /// This function:
 /// 1. Stores the current clipboard content
 /// 2. Puts the provided text into the clipboard
-/// 3. Simulates Ctrl+V to paste the text
+/// 3. Simulates the paste keys (Ctrl+V unless configured otherwise)
 /// 4. Restores the original clipboard content
-pub fn insert_text(text: &str) {
-    println!("Inserting text via clipboard: {}", text);
+pub fn insert_text(text: &str, settings: &Insertion) -> Result<(), String> {
+    println!("Inserting {} characters via clipboard", text.chars().count());
+
+    let chord = parse_keys(&settings.paste_keys)?;
 
     // Create clipboard instance
-    let mut clipboard = Clipboard::new().expect("Failed to initialize clipboard");
+    let mut clipboard =
+        Clipboard::new().map_err(|e| format!("Failed to initialize clipboard: {}", e))?;
 
     // Store current clipboard content (if any)
     let original_content = clipboard.get_text().ok();
@@ -22,38 +27,68 @@ pub fn insert_text(text: &str) {
     // Set the new text to clipboard
     clipboard
         .set_text(text)
-        .expect("Failed to set clipboard content");
+        .map_err(|e| format!("Failed to set clipboard content: {}", e))?;
 
     // Give the system a moment to register new clipboard contents
-    thread::sleep(Duration::from_millis(120));
+    thread::sleep(Duration::from_millis(settings.clipboard_delay_ms));
 
-    // Best-effort: ensure common modifiers aren't left logically pressed
-    let _ = simulate(&EventType::KeyRelease(Key::ControlLeft));
-    let _ = simulate(&EventType::KeyRelease(Key::ControlRight));
-    let _ = simulate(&EventType::KeyRelease(Key::ShiftLeft));
-    let _ = simulate(&EventType::KeyRelease(Key::ShiftRight));
-    let _ = simulate(&EventType::KeyRelease(Key::Alt));
+    release_modifiers();
 
-    // Paste via Ctrl+V with small inter-event delays for reliability
-    paste_ctrl_v();
+    // Paste with small inter-event delays for reliability
+    press_chord(&chord);
 
     // Some apps (e.g., certain IDEs) evaluate the clipboard on key release; wait
-    thread::sleep(Duration::from_millis(350));
+    thread::sleep(Duration::from_millis(settings.paste_delay_ms));
 
     // Restore original clipboard content if there was any
     if let Some(content) = original_content {
         // A tiny delay to de-couple from paste completion in slow apps
-        thread::sleep(Duration::from_millis(50));
+        thread::sleep(Duration::from_millis(settings.restore_delay_ms));
         let _ = clipboard.set_text(content);
     }
+    Ok(())
 }
 
-fn paste_ctrl_v() {
-    let _ = simulate(&EventType::KeyPress(Key::ControlLeft));
-    thread::sleep(Duration::from_millis(20));
-    let _ = simulate(&EventType::KeyPress(Key::KeyV));
-    thread::sleep(Duration::from_millis(30));
-    let _ = simulate(&EventType::KeyRelease(Key::KeyV));
-    thread::sleep(Duration::from_millis(20));
+/// Best-effort: ensure common modifiers aren't left logically pressed
+pub fn release_modifiers() {
     let _ = simulate(&EventType::KeyRelease(Key::ControlLeft));
+    let _ = simulate(&EventType::KeyRelease(Key::ControlRight));
+    let _ = simulate(&EventType::KeyRelease(Key::ShiftLeft));
+    let _ = simulate(&EventType::KeyRelease(Key::ShiftRight));
+    let _ = simulate(&EventType::KeyRelease(Key::Alt));
+}
+
+// Keys of a chord like "ctrl+shift+v", modifiers first
+fn parse_keys(keys: &str) -> Result<Vec<Key>, String> {
+    let chord = keys
+        .split('+')
+        .map(|name| match name.trim().to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => Ok(Key::ControlLeft),
+            "shift" => Ok(Key::ShiftLeft),
+            "alt" => Ok(Key::Alt),
+            "super" | "meta" => Ok(Key::MetaLeft),
+            "v" => Ok(Key::KeyV),
+            "insert" => Ok(Key::Insert),
+            other => Err(format!(
+                "Unsupported key '{}' in paste keys '{}'",
+                other, keys
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if chord.is_empty() {
+        return Err("No paste keys configured".to_string());
+    }
+    Ok(chord)
+}
+
+fn press_chord(chord: &[Key]) {
+    for key in chord {
+        let _ = simulate(&EventType::KeyPress(*key));
+        thread::sleep(Duration::from_millis(20));
+    }
+    thread::sleep(Duration::from_millis(10));
+    for key in chord.iter().rev() {
+        let _ = simulate(&EventType::KeyRelease(*key));
+        thread::sleep(Duration::from_millis(20));
+    }
 }
@@ -0,0 +1,189 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use crate::config;
+
+/// Environment variable that points battery detection at another sysfs tree (e.g. a fixture)
+pub const SYSFS_ROOT_ENV: &str = "VOICE_INPUT_SYSFS_ROOT";
+
+/// Number of CPUs the process may run on
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(4)
+}
+
+fn sysfs_root() -> PathBuf {
+    std::env::var_os(SYSFS_ROOT_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/sys"))
+}
+
+fn read_attr(dir: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(dir.join(name))
+        .ok()
+        .map(|v| v.trim().to_string())
+}
+
+/// Whether the machine runs on battery: a battery is discharging and no AC adapter is online.
+/// Machines without `power_supply` entries (desktops, containers) count as on AC power.
+pub fn on_battery() -> bool {
+    on_battery_in(&sysfs_root())
+}
+
+/// `on_battery` against the sysfs tree at `root`
+pub fn on_battery_in(root: &Path) -> bool {
+    let Ok(entries) = fs::read_dir(root.join("class/power_supply")) else {
+        return false;
+    };
+    let mut discharging = false;
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        match read_attr(&dir, "type").as_deref() {
+            Some("Mains") | Some("USB") if read_attr(&dir, "online").as_deref() == Some("1") => {
+                return false;
+            }
+            Some("Battery") if read_attr(&dir, "status").as_deref() == Some("Discharging") => {
+                discharging = true;
+            }
+            _ => {}
+        }
+    }
+    discharging
+}
+
+/// Decoding threads for the current power state
+pub fn decode_threads() -> usize {
+    let compute = config::get_compute();
+    if compute.battery_threads > 0 && on_battery() {
+        return compute.battery_threads;
+    }
+    if compute.threads > 0 {
+        compute.threads
+    } else {
+        available_threads()
+    }
+}
+
+/// The model to use instead of `selected_model` on battery, if configured
+pub fn battery_model() -> Option<String> {
+    config::get_compute()
+        .battery_model
+        .filter(|m| !m.trim().is_empty())
+        .filter(|_| on_battery())
+}
+
+/// Lower the scheduling priority of the calling thread (and threads it spawns afterwards).
+/// Linux applies niceness per thread; elsewhere this is a no-op.
+pub fn lower_thread_priority() {
+    #[cfg(target_os = "linux")]
+    unsafe {
+        extern "C" {
+            fn setpriority(which: i32, who: u32, prio: i32) -> i32;
+        }
+        // PRIO_PROCESS with id 0 is the calling thread on Linux; nice 10 is "background"
+        if setpriority(0, 0, 10) != 0 {
+            eprintln!("Failed to lower decoding thread priority");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `power_supply` entry: directory name and its attributes
+    type Supply = (&'static str, &'static [(&'static str, &'static str)]);
+
+    const AC_ONLINE: Supply = ("AC", &[("type", "Mains"), ("online", "1")]);
+    const AC_OFFLINE: Supply = ("AC", &[("type", "Mains"), ("online", "0")]);
+    const USB_ONLINE: Supply = ("ucsi-source-psy-1", &[("type", "USB"), ("online", "1")]);
+    const DISCHARGING: Supply = ("BAT0", &[("type", "Battery"), ("status", "Discharging\n")]);
+    const CHARGING: Supply = ("BAT0", &[("type", "Battery"), ("status", "Charging")]);
+    const FULL: Supply = ("BAT1", &[("type", "Battery"), ("status", "Full")]);
+
+    /// Write a fake sysfs tree with the given power supplies under a fresh directory
+    fn fake_sysfs(name: &str, supplies: &[Supply]) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("voice_input_sysfs_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        for (dir, attrs) in supplies {
+            let dir = root.join("class/power_supply").join(dir);
+            fs::create_dir_all(&dir).unwrap();
+            for (attr, value) in *attrs {
+                fs::write(dir.join(attr), value).unwrap();
+            }
+        }
+        root
+    }
+
+    #[test]
+    fn power_state() {
+        let cases: &[(&str, &[Supply], bool)] = &[
+            ("no_supplies", &[], false),
+            ("ac_only", &[AC_ONLINE], false),
+            ("discharging", &[AC_OFFLINE, DISCHARGING], true),
+            ("battery_only", &[DISCHARGING], true),
+            ("charging", &[AC_ONLINE, CHARGING], false),
+            ("full_unplugged", &[AC_OFFLINE, FULL], false),
+            ("usb_power", &[USB_ONLINE, DISCHARGING], false),
+            ("second_battery", &[AC_OFFLINE, FULL, DISCHARGING], true),
+        ];
+        for (name, supplies, expected) in cases {
+            let root = fake_sysfs(name, supplies);
+            assert_eq!(on_battery_in(&root), *expected, "{}", name);
+            let _ = fs::remove_dir_all(&root);
+        }
+        assert!(!on_battery_in(Path::new("/nonexistent/sysfs")));
+    }
+
+    #[test]
+    fn decode_threads_follow_power_state() {
+        let _lock = config::TEST_CONFIG_LOCK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let config_file =
+            std::env::temp_dir().join(format!("voice_input_compute_{}.json", std::process::id()));
+        let _config = config::use_config_file(Some(config_file.clone()));
+        let on_ac = fake_sysfs("decode_ac", &[AC_ONLINE, CHARGING]);
+        let on_battery = fake_sysfs("decode_battery", &[AC_OFFLINE, DISCHARGING]);
+
+        // (threads, battery_threads, sysfs, expected; 0 = one per available CPU)
+        let cases: &[(usize, usize, &Path, usize)] = &[
+            (0, 0, &on_ac, 0),
+            (0, 0, &on_battery, 0),
+            (6, 0, &on_ac, 6),
+            (6, 0, &on_battery, 6),
+            (6, 2, &on_ac, 6),
+            (6, 2, &on_battery, 2),
+            (0, 2, &on_ac, 0),
+            (0, 2, &on_battery, 2),
+        ];
+        for (threads, battery_threads, sysfs, expected) in cases {
+            let settings = format!(
+                r#"{{"selected_model": "small", "compute": {{"threads": {}, "battery_threads": {}}}}}"#,
+                threads, battery_threads
+            );
+            fs::write(&config_file, settings).unwrap();
+            std::env::set_var(SYSFS_ROOT_ENV, sysfs);
+            let expected = if *expected == 0 {
+                available_threads()
+            } else {
+                *expected
+            };
+            assert_eq!(
+                decode_threads(),
+                expected,
+                "threads {}, battery_threads {}, {}",
+                threads,
+                battery_threads,
+                sysfs.display()
+            );
+        }
+
+        std::env::remove_var(SYSFS_ROOT_ENV);
+        let _ = fs::remove_file(&config_file);
+        let _ = fs::remove_dir_all(&on_ac);
+        let _ = fs::remove_dir_all(&on_battery);
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use crate::model_registry;
+use crate::whisper::{WhisperTranscriber, DOWNLOAD_CANCELLED};
+
+/// Updates about model downloads, reported per model (all of its files combined)
+#[derive(Debug, Clone)]
+pub enum DownloadEvent {
+    Progress {
+        model: String,
+        percent: u8,
+        eta_secs: u64,
+    },
+    Finished {
+        model: String,
+    },
+    Failed {
+        model: String,
+        error: String,
+    },
+    Cancelled {
+        model: String,
+    },
+}
+
+// One file of a model download
+struct FileJob {
+    file_name: String,
+    downloaded: AtomicU64,
+    total: AtomicU64,
+    // Bytes already on disk when this session started (resumed download); u64::MAX until known
+    resumed_from: AtomicU64,
+}
+
+// All files needed by one model (e.g. English-only and multilingual variants)
+struct ModelJob {
+    files: Vec<FileJob>,
+    cancel: AtomicBool,
+    started: Instant,
+}
+
+impl ModelJob {
+    // Combined (percent, eta) over all files. Files that haven't started yet count
+    // with their registry size so the bar doesn't jump back when the next file begins.
+    fn progress(&self) -> (u8, u64) {
+        let downloaded: u64 = self
+            .files
+            .iter()
+            .map(|f| f.downloaded.load(Ordering::SeqCst))
+            .sum();
+        let total: u64 = self.files.iter().map(|f| f.total.load(Ordering::SeqCst)).sum();
+        if total == 0 {
+            return (0, 0);
+        }
+        let percent = ((downloaded as f64 / total as f64) * 100.0).clamp(0.0, 100.0) as u8;
+
+        let elapsed = self.started.elapsed().as_secs_f64();
+        // Only bytes fetched in this session tell the transfer rate
+        let fetched: u64 = self
+            .files
+            .iter()
+            .map(|f| match f.resumed_from.load(Ordering::SeqCst) {
+                u64::MAX => 0,
+                from => f.downloaded.load(Ordering::SeqCst).saturating_sub(from),
+            })
+            .sum();
+        let fetched = fetched as f64;
+        let eta_secs = if elapsed > 0.0 && fetched > 0.0 {
+            let rate = fetched / elapsed;
+            (total.saturating_sub(downloaded) as f64 / rate).round() as u64
+        } else {
+            0
+        };
+        (percent, eta_secs)
+    }
+}
+
+/// Runs model downloads in background threads, one job per model with one entry per file.
+/// Progress, completion, failures and cancellations are reported through `notify`.
+pub struct DownloadManager {
+    jobs: Mutex<HashMap<String, Arc<ModelJob>>>,
+    notify: Box<dyn Fn(DownloadEvent) + Send + Sync>,
+}
+
+impl DownloadManager {
+    pub fn new(notify: Box<dyn Fn(DownloadEvent) + Send + Sync>) -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            notify,
+        }
+    }
+
+    /// Start downloading `files` for `model`. Returns false if a download for it is already running.
+    pub fn start(self: &Arc<Self>, model: &str, files: Vec<String>) -> bool {
+        let job = {
+            let mut jobs = self.jobs.lock().unwrap();
+            if jobs.contains_key(model) {
+                return false;
+            }
+            let job = Arc::new(ModelJob {
+                files: files
+                    .into_iter()
+                    .map(|file_name| {
+                        let estimate = model_registry::find_by_file(&file_name)
+                            .map(|m| m.size_mb * 1024 * 1024)
+                            .unwrap_or(0);
+                        FileJob {
+                            file_name,
+                            downloaded: AtomicU64::new(0),
+                            total: AtomicU64::new(estimate),
+                            resumed_from: AtomicU64::new(u64::MAX),
+                        }
+                    })
+                    .collect(),
+                cancel: AtomicBool::new(false),
+                started: Instant::now(),
+            });
+            jobs.insert(model.to_string(), job.clone());
+            job
+        };
+
+        let manager = self.clone();
+        let model = model.to_string();
+        thread::spawn(move || {
+            let result = manager.run_job(&model, &job);
+            manager.jobs.lock().unwrap().remove(&model);
+            let event = match result {
+                Ok(()) => DownloadEvent::Finished { model },
+                Err(_) if job.cancel.load(Ordering::SeqCst) => {
+                    println!("Download of model '{}' cancelled", model);
+                    DownloadEvent::Cancelled { model }
+                }
+                Err(error) => {
+                    eprintln!("Download of model '{}' failed: {}", model, error);
+                    DownloadEvent::Failed { model, error }
+                }
+            };
+            (manager.notify)(event);
+        });
+        true
+    }
+
+    /// Request cancellation of the download for `model`. Returns false if none is running.
+    pub fn cancel(&self, model: &str) -> bool {
+        match self.jobs.lock().unwrap().get(model) {
+            Some(job) => {
+                job.cancel.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Download the files of a job one after another
+    fn run_job(&self, model: &str, job: &ModelJob) -> Result<(), String> {
+        let last_percent = AtomicU64::new(u64::MAX);
+        for file in &job.files {
+            if job.cancel.load(Ordering::SeqCst) {
+                return Err(DOWNLOAD_CANCELLED.to_string());
+            }
+            let on_progress = |downloaded: u64, total: u64| {
+                // The first report of a file tells how much was already on disk
+                let _ = file.resumed_from.compare_exchange(
+                    u64::MAX,
+                    downloaded,
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                );
+                file.downloaded.store(downloaded, Ordering::SeqCst);
+                if total > 0 {
+                    file.total.store(total, Ordering::SeqCst);
+                }
+                let (percent, eta_secs) = job.progress();
+                if last_percent.swap(percent as u64, Ordering::SeqCst) != percent as u64 {
+                    (self.notify)(DownloadEvent::Progress {
+                        model: model.to_string(),
+                        percent,
+                        eta_secs,
+                    });
+                }
+            };
+            WhisperTranscriber::download_model_with(&file.file_name, &on_progress, &job.cancel)?;
+
+            // Count the finished file as complete even if the server didn't report its size
+            let done = file.downloaded.load(Ordering::SeqCst).max(file.total.load(Ordering::SeqCst));
+            file.downloaded.store(done, Ordering::SeqCst);
+            file.total.store(done, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,464 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::audio_utils;
+use crate::bench::{collect_wav_files, load_wav_16k};
+use crate::config;
+use crate::model_cache::{CacheSettings, ModelCache};
+use crate::transcriber_utils::{
+    ensure_engine_for, run_pipeline, select_model_file, PipelineOptions,
+};
+
+const USAGE: &str = "Usage: voice_input eval <dir> [options]
+  <dir>                 Directory of WAV files, each with a reference transcript <name>.txt
+  --config <file>       Config to evaluate (default: your config.json)
+  --compare <file>      Second config; results are shown side by side with the difference
+  --language <code>     Language of the recordings (default: en)
+  --keep-case           Don't lowercase before comparing
+  --keep-punctuation    Don't strip punctuation before comparing
+  --replace <a=b>       Normalize spelling variants, e.g. --replace colour=color (repeatable)
+  --json                Print results as JSON instead of a table";
+
+/// Text normalization applied to both reference and hypothesis before scoring
+#[derive(Debug, Clone)]
+pub struct TextNormalizer {
+    pub lowercase: bool,
+    pub strip_punctuation: bool,
+    /// Whole-word replacements applied after case/punctuation handling
+    pub replacements: Vec<(String, String)>,
+}
+
+impl Default for TextNormalizer {
+    fn default() -> Self {
+        Self {
+            lowercase: true,
+            strip_punctuation: true,
+            replacements: Vec::new(),
+        }
+    }
+}
+
+impl TextNormalizer {
+    /// Normalized words of `text`
+    pub fn words(&self, text: &str) -> Vec<String> {
+        let text = if self.lowercase {
+            text.to_lowercase()
+        } else {
+            text.to_string()
+        };
+        let text: String = if self.strip_punctuation {
+            // Keep apostrophes inside words ("don't") and treat other punctuation as spaces
+            text.chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || c == '\'' || c.is_whitespace() {
+                        c
+                    } else {
+                        ' '
+                    }
+                })
+                .collect()
+        } else {
+            text
+        };
+        text.split_whitespace()
+            .map(|w| if self.strip_punctuation { w.trim_matches('\'') } else { w })
+            .filter(|w| !w.is_empty())
+            .map(|w| {
+                self.replacements
+                    .iter()
+                    .find(|(from, _)| from == w)
+                    .map(|(_, to)| to.clone())
+                    .unwrap_or_else(|| w.to_string())
+            })
+            .filter(|w| !w.is_empty())
+            .collect()
+    }
+}
+
+/// Levenshtein distance between two token sequences (substitutions + deletions + insertions)
+pub fn edit_distance<T: PartialEq>(reference: &[T], hypothesis: &[T]) -> usize {
+    let mut prev: Vec<usize> = (0..=hypothesis.len()).collect();
+    let mut cur = vec![0; hypothesis.len() + 1];
+    for (i, r) in reference.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, h) in hypothesis.iter().enumerate() {
+            let substitution = prev[j] + usize::from(r != h);
+            cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[hypothesis.len()]
+}
+
+/// Word and character errors of one hypothesis against its reference
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ErrorCounts {
+    pub word_errors: usize,
+    pub ref_words: usize,
+    pub char_errors: usize,
+    pub ref_chars: usize,
+}
+
+impl ErrorCounts {
+    pub fn measure(normalizer: &TextNormalizer, reference: &str, hypothesis: &str) -> Self {
+        let ref_words = normalizer.words(reference);
+        let hyp_words = normalizer.words(hypothesis);
+        let ref_chars: Vec<char> = ref_words.join(" ").chars().collect();
+        let hyp_chars: Vec<char> = hyp_words.join(" ").chars().collect();
+        Self {
+            word_errors: edit_distance(&ref_words, &hyp_words),
+            ref_words: ref_words.len(),
+            char_errors: edit_distance(&ref_chars, &hyp_chars),
+            ref_chars: ref_chars.len(),
+        }
+    }
+
+    pub fn add(&mut self, other: &ErrorCounts) {
+        self.word_errors += other.word_errors;
+        self.ref_words += other.ref_words;
+        self.char_errors += other.char_errors;
+        self.ref_chars += other.ref_chars;
+    }
+
+    pub fn wer(&self) -> f64 {
+        self.word_errors as f64 / self.ref_words.max(1) as f64
+    }
+
+    pub fn cer(&self) -> f64 {
+        self.char_errors as f64 / self.ref_chars.max(1) as f64
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FileResult {
+    file: String,
+    reference: String,
+    hypothesis: String,
+    wer: f64,
+    cer: f64,
+    counts: ErrorCounts,
+}
+
+#[derive(Debug, Serialize)]
+struct RunResult {
+    config: String,
+    files: Vec<FileResult>,
+    wer: f64,
+    cer: f64,
+}
+
+struct EvalOptions {
+    dir: PathBuf,
+    config: Option<PathBuf>,
+    compare: Option<PathBuf>,
+    language: String,
+    normalizer: TextNormalizer,
+    json: bool,
+}
+
+fn parse_options(args: &[&str]) -> Result<EvalOptions, String> {
+    let mut dir = None;
+    let mut options = EvalOptions {
+        dir: PathBuf::new(),
+        config: None,
+        compare: None,
+        language: "en".to_string(),
+        normalizer: TextNormalizer::default(),
+        json: false,
+    };
+
+    let mut iter = args.iter();
+    while let Some(&arg) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .map(|v| v.to_string())
+                .ok_or_else(|| format!("Missing value for {}\n\n{}", arg, USAGE))
+        };
+        match arg {
+            "--config" => options.config = Some(PathBuf::from(value()?)),
+            "--compare" => options.compare = Some(PathBuf::from(value()?)),
+            "--language" => options.language = value()?,
+            "--keep-case" => options.normalizer.lowercase = false,
+            "--keep-punctuation" => options.normalizer.strip_punctuation = false,
+            "--replace" => {
+                let pair = value()?;
+                let (from, to) = pair
+                    .split_once('=')
+                    .ok_or_else(|| format!("--replace expects a=b, got '{}'", pair))?;
+                options
+                    .normalizer
+                    .replacements
+                    .push((from.trim().to_lowercase(), to.trim().to_lowercase()));
+            }
+            "--json" => options.json = true,
+            "--help" | "-h" => return Err(USAGE.to_string()),
+            other if other.starts_with("--") => {
+                return Err(format!("Unknown option {}\n\n{}", other, USAGE))
+            }
+            other => dir = Some(PathBuf::from(other)),
+        }
+    }
+    options.dir = dir.ok_or_else(|| USAGE.to_string())?;
+    Ok(options)
+}
+
+// Audio files that have a reference transcript next to them
+fn load_cases(dir: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+    let mut cases = Vec::new();
+    for audio in collect_wav_files(&[dir.to_path_buf()])? {
+        let reference_path = audio.with_extension("txt");
+        match fs::read_to_string(&reference_path) {
+            Ok(reference) => cases.push((audio, reference.trim().to_string())),
+            Err(_) => eprintln!("Skipping {}: no {}", audio.display(), reference_path.display()),
+        }
+    }
+    if cases.is_empty() {
+        return Err(format!(
+            "No WAV files with reference .txt transcripts in {}",
+            dir.display()
+        ));
+    }
+    Ok(cases)
+}
+
+// Run every case through the full dictation pipeline with the settings of `config_file`
+fn run_config(
+    config_file: Option<&Path>,
+    cases: &[(PathBuf, String)],
+    options: &EvalOptions,
+) -> Result<RunResult, String> {
+    let _config = config::use_config_file(config_file.map(Path::to_path_buf));
+    let label = config_file
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|| "config.json".to_string());
+    println!("Evaluating with {}", label);
+
+    let is_english = options.language.starts_with("en");
+    let model_file = select_model_file(&config::get_selected_model(), is_english);
+    let translate = config::get_translate_enabled();
+    let cache = Arc::new(ModelCache::new(CacheSettings::from_config()));
+    let engine = ensure_engine_for(is_english, &model_file, &cache)?;
+
+    let mut total = ErrorCounts::default();
+    let mut files = Vec::new();
+    for (audio, reference) in cases {
+        let samples = load_wav_16k(audio)?;
+        let hypothesis = run_pipeline(
+            &*engine,
+            &samples,
+            audio_utils::WHISPER_SAMPLE_RATE,
+            1,
+            &options.language,
+            &PipelineOptions {
+                translate,
+                ..Default::default()
+            },
+        )
+        .map_err(|e| format!("{}: {}", audio.display(), e))?;
+        let hypothesis = hypothesis.lines().map(str::trim).collect::<Vec<_>>().join(" ");
+
+        let counts = ErrorCounts::measure(&options.normalizer, reference, &hypothesis);
+        total.add(&counts);
+        files.push(FileResult {
+            file: audio
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            reference: reference.clone(),
+            hypothesis,
+            wer: counts.wer(),
+            cer: counts.cer(),
+            counts,
+        });
+    }
+
+    Ok(RunResult {
+        config: label,
+        files,
+        wer: total.wer(),
+        cer: total.cer(),
+    })
+}
+
+fn print_table(runs: &[RunResult]) {
+    let percent = |v: f64| format!("{:.1}%", v * 100.0);
+    match runs {
+        [run] => {
+            println!("\n{:<32} {:>8} {:>8}", "FILE", "WER", "CER");
+            for f in &run.files {
+                println!("{:<32} {:>8} {:>8}", f.file, percent(f.wer), percent(f.cer));
+            }
+            println!("{:<32} {:>8} {:>8}", "TOTAL", percent(run.wer), percent(run.cer));
+        }
+        [a, b] => {
+            println!("\nA: {}\nB: {}", a.config, b.config);
+            println!(
+                "\n{:<32} {:>8} {:>8} {:>8} {:>8} {:>8}",
+                "FILE", "WER A", "WER B", "ΔWER", "CER A", "CER B"
+            );
+            for (fa, fb) in a.files.iter().zip(&b.files) {
+                println!(
+                    "{:<32} {:>8} {:>8} {:>+7.1}% {:>8} {:>8}",
+                    fa.file,
+                    percent(fa.wer),
+                    percent(fb.wer),
+                    (fb.wer - fa.wer) * 100.0,
+                    percent(fa.cer),
+                    percent(fb.cer)
+                );
+            }
+            println!(
+                "{:<32} {:>8} {:>8} {:>+7.1}% {:>8} {:>8}",
+                "TOTAL",
+                percent(a.wer),
+                percent(b.wer),
+                (b.wer - a.wer) * 100.0,
+                percent(a.cer),
+                percent(b.cer)
+            );
+            // Show what changed where the configurations disagree
+            for (fa, fb) in a.files.iter().zip(&b.files) {
+                if fa.hypothesis != fb.hypothesis {
+                    println!(
+                        "\n{}\n  ref: {}\n  A:   {}\n  B:   {}",
+                        fa.file, fa.reference, fa.hypothesis, fb.hypothesis
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `voice_input eval`: word/character error rates of the dictation pipeline against references
+pub fn run(args: &[&str]) -> Result<(), String> {
+    let options = parse_options(args)?;
+    for file in options.config.iter().chain(&options.compare) {
+        config::check_config_file(file)?;
+    }
+    let cases = load_cases(&options.dir)?;
+    println!("{} recordings with reference transcripts", cases.len());
+
+    let mut runs = vec![run_config(options.config.as_deref(), &cases, &options)?];
+    if let Some(compare) = &options.compare {
+        runs.push(run_config(Some(compare), &cases, &options)?);
+    }
+
+    if options.json {
+        let json = serde_json::to_string_pretty(&runs).map_err(|e| e.to_string())?;
+        println!("{}", json);
+    } else {
+        print_table(&runs);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<&str> {
+        text.split_whitespace().collect()
+    }
+
+    #[test]
+    fn word_edit_distance() {
+        // (reference, hypothesis, errors)
+        let cases = [
+            ("the cat sat", "the cat sat", 0),
+            ("the cat sat", "the bat sat", 1),
+            ("the cat sat", "the cat sat down", 1),
+            ("the cat sat", "cat sat", 1),
+            ("the cat sat on the mat", "a cat sat the mat today", 3),
+            ("one two three", "three two one", 2),
+            ("", "", 0),
+            ("", "two words", 2),
+            ("three words here", "", 3),
+        ];
+        for (reference, hypothesis, expected) in cases {
+            assert_eq!(
+                edit_distance(&words(reference), &words(hypothesis)),
+                expected,
+                "{:?} vs {:?}",
+                reference,
+                hypothesis
+            );
+        }
+    }
+
+    #[test]
+    fn error_counts() {
+        let normalizer = TextNormalizer::default();
+        let counts = ErrorCounts::measure(&normalizer, "The cat sat.", "the bat sat");
+        assert_eq!((counts.word_errors, counts.ref_words), (1, 3));
+        assert_eq!((counts.char_errors, counts.ref_chars), (1, 11));
+        assert!((counts.wer() - 1.0 / 3.0).abs() < 1e-9);
+
+        // An empty reference counts every hypothesis word as an error
+        let counts = ErrorCounts::measure(&normalizer, "", "two words");
+        assert_eq!((counts.word_errors, counts.ref_words), (2, 0));
+        assert_eq!(counts.wer(), 2.0);
+        let counts = ErrorCounts::measure(&normalizer, "", "");
+        assert_eq!((counts.wer(), counts.cer()), (0.0, 0.0));
+        // An empty hypothesis deletes every reference word
+        let counts = ErrorCounts::measure(&normalizer, "three words here", "");
+        assert_eq!((counts.wer(), counts.cer()), (1.0, 1.0));
+    }
+
+    #[test]
+    fn aggregate_wer_is_weighted_by_reference_length() {
+        let normalizer = TextNormalizer::default();
+        let short = ErrorCounts::measure(&normalizer, "yes", "no");
+        let long = ErrorCounts::measure(
+            &normalizer,
+            "one two three four five six seven eight nine",
+            "one two three four five six seven eight nine",
+        );
+        let mut total = ErrorCounts::default();
+        total.add(&short);
+        total.add(&long);
+        assert_eq!((total.word_errors, total.ref_words), (1, 10));
+        // Not the mean of the per-file rates (0.5)
+        assert!((total.wer() - 0.1).abs() < 1e-9);
+        assert_eq!(ErrorCounts::default().wer(), 0.0);
+    }
+
+    #[test]
+    fn normalization_options() {
+        let options = |args: &[&str]| {
+            let mut args = args.to_vec();
+            args.push("recordings");
+            parse_options(&args).unwrap().normalizer
+        };
+        let text = "Don't stop, 'Bob' - it's the U.S. Colour!";
+        // (options, normalized words)
+        let cases: [(&[&str], &[&str]); 4] = [
+            (
+                &[],
+                &["don't", "stop", "bob", "it's", "the", "u", "s", "colour"],
+            ),
+            (
+                &["--keep-case"],
+                &["Don't", "stop", "Bob", "it's", "the", "U", "S", "Colour"],
+            ),
+            (
+                &["--keep-punctuation"],
+                &[
+                    "don't", "stop,", "'bob'", "-", "it's", "the", "u.s.", "colour!",
+                ],
+            ),
+            (
+                &["--replace", "Colour=color", "--replace", "u=us"],
+                &["don't", "stop", "bob", "it's", "the", "us", "s", "color"],
+            ),
+        ];
+        for (args, expected) in cases {
+            assert_eq!(options(args).words(text), expected, "{:?}", args);
+        }
+
+        assert!(parse_options(&["--replace", "colour", "recordings"]).is_err());
+        assert!(parse_options(&["--keep-case"]).is_err());
+    }
+}
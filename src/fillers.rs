@@ -0,0 +1,334 @@
+use std::cmp::Reverse;
+
+use crate::config::Fillers;
+use crate::post_processing::TextStage;
+use crate::text_normalization;
+
+// Built-in lists per language: hesitation sounds, filler phrases, words that may repeat
+struct BuiltinFillers {
+    hesitations: &'static [&'static str],
+    discourse: &'static [&'static str],
+    keep_repeats: &'static [&'static str],
+}
+
+const ENGLISH: BuiltinFillers = BuiltinFillers {
+    hesitations: &["um", "uh", "uhm", "erm", "hmm", "mmm"],
+    discourse: &["you know", "i mean", "like", "basically", "well", "so yeah"],
+    keep_repeats: &[
+        "that", "had", "is", "no", "yes", "very", "really", "so", "bye", "ha",
+    ],
+};
+
+const RUSSIAN: BuiltinFillers = BuiltinFillers {
+    hesitations: &["э", "эм", "хм", "гм", "ммм"],
+    discourse: &[
+        "ну",
+        "типа",
+        "короче",
+        "как бы",
+        "в общем",
+        "значит",
+        "вот",
+        "это самое",
+        "так сказать",
+    ],
+    keep_repeats: &[
+        "да", "нет", "так", "очень", "ха", "давай", "пока", "еле", "едва",
+    ],
+};
+
+const NO_FILLERS: BuiltinFillers = BuiltinFillers {
+    hesitations: &[],
+    discourse: &[],
+    keep_repeats: &[],
+};
+
+/// Remove hesitation sounds and stutter repeats ("the the"); in aggressive mode also
+/// filler phrases set off by punctuation, and repeated phrases
+pub struct FillerRemoval {
+    language: String,
+    aggressive: bool,
+    hesitations: Vec<Vec<String>>,
+    discourse: Vec<Vec<String>>,
+    keep_repeats: Vec<String>,
+}
+
+impl FillerRemoval {
+    /// The configured (or built-in) lists for `language`
+    pub fn for_language(settings: &Fillers, language: &str) -> Self {
+        let code = language.get(..2).unwrap_or(language).to_lowercase();
+        let builtin = match code.as_str() {
+            "en" => &ENGLISH,
+            "ru" => &RUSSIAN,
+            _ => &NO_FILLERS,
+        };
+        let configured = settings.languages.get(&code).cloned().unwrap_or_default();
+        let list = |configured: Option<Vec<String>>, builtin: &[&str]| -> Vec<String> {
+            configured
+                .unwrap_or_else(|| builtin.iter().map(|w| w.to_string()).collect())
+                .iter()
+                .map(|w| w.trim().to_lowercase())
+                .filter(|w| !w.is_empty())
+                .collect()
+        };
+        let phrases = |words: Vec<String>| -> Vec<Vec<String>> {
+            let mut phrases: Vec<Vec<String>> = words
+                .iter()
+                .map(|p| p.split_whitespace().map(str::to_string).collect())
+                .collect();
+            phrases.sort_by_key(|p| Reverse(p.len()));
+            phrases
+        };
+        Self {
+            aggressive: settings.mode == "aggressive",
+            hesitations: phrases(list(configured.hesitations, builtin.hesitations)),
+            discourse: phrases(list(configured.discourse, builtin.discourse)),
+            keep_repeats: list(configured.keep_repeats, builtin.keep_repeats),
+            language: code,
+        }
+    }
+
+    fn apply_line(&self, line: &str) -> String {
+        let mut words: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+
+        let mut i = 0;
+        while i < words.len() {
+            let len = self
+                .hesitation_at(&words, i)
+                .or_else(|| self.discourse_at(&words, i))
+                .or_else(|| self.repeat_at(&words, i));
+            match len {
+                // Look at the same position again: "the the the", "um, uh"
+                Some(len) => remove(&mut words, i, len),
+                None => i += 1,
+            }
+        }
+        words.join(" ")
+    }
+
+    // Length of the hesitation sound starting at `words[i]`
+    fn hesitation_at(&self, words: &[String], i: usize) -> Option<usize> {
+        self.hesitations.iter().find_map(|phrase| {
+            let candidate = words.get(i..i + phrase.len())?;
+            candidate
+                .iter()
+                .zip(phrase)
+                .all(|(word, entry)| is_drawn_out(&core(word).to_lowercase(), entry))
+                .then_some(phrase.len())
+        })
+    }
+
+    // Length of the filler phrase starting at `words[i]`, if it is set off from the sentence
+    fn discourse_at(&self, words: &[String], i: usize) -> Option<usize> {
+        if !self.aggressive {
+            return None;
+        }
+        let len = self.discourse.iter().find_map(|phrase| {
+            let candidate = words.get(i..i + phrase.len())?;
+            candidate
+                .iter()
+                .zip(phrase)
+                .all(|(word, entry)| core(word).to_lowercase() == *entry)
+                .then_some(phrase.len())
+        })?;
+        let before_pause = trail(&words[i + len - 1]).contains([',', ';', ':']);
+        // At a sentence start only with a pause after it: "Like, I said" but not "Like I said"
+        if starts_sentence(words, i) {
+            return before_pause.then_some(len);
+        }
+        let after_pause = trail(&words[i - 1]).contains([',', ';', ':']);
+        (after_pause || before_pause).then_some(len)
+    }
+
+    // Length of the first copy of a phrase repeated right after itself
+    fn repeat_at(&self, words: &[String], i: usize) -> Option<usize> {
+        let longest = if self.aggressive { 3 } else { 1 };
+        (1..=longest).rev().find(|&n| {
+            let (Some(first), Some(second)) = (words.get(i..i + n), words.get(i + n..i + 2 * n))
+            else {
+                return false;
+            };
+            // "Stop. Stop." is said twice on purpose
+            if ends_sentence(&first[n - 1]) {
+                return false;
+            }
+            let same = first
+                .iter()
+                .zip(second)
+                .all(|(a, b)| core(a).to_lowercase() == core(b).to_lowercase());
+            same && first.iter().all(|w| self.may_collapse(w))
+        })
+    }
+
+    // Numbers ("five five five") and words like "that that" are meant to repeat
+    fn may_collapse(&self, word: &str) -> bool {
+        let word = core(word).to_lowercase();
+        !word.is_empty()
+            && !word.contains(|c: char| c.is_ascii_digit())
+            && !self.keep_repeats.contains(&word)
+            && !text_normalization::is_number_word(&self.language, &word)
+    }
+}
+
+impl TextStage for FillerRemoval {
+    fn apply(&self, text: &str) -> String {
+        text.lines()
+            .map(|line| self.apply_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// Drop `words[i..i + len]`, keeping the sentence punctuation and capitalization around it
+fn remove(words: &mut Vec<String>, i: usize, len: usize) {
+    let removed: Vec<String> = words.drain(i..i + len).collect();
+    let first = &removed[0];
+    let last = &removed[len - 1];
+
+    if i > 0 {
+        let end: String = trail(last)
+            .chars()
+            .filter(|c| matches!(c, '.' | '?' | '!' | '…'))
+            .collect();
+        // "I think, um." -> "I think."; "and then, uh" -> "and then"; "this, uh, works" -> "this works"
+        let parenthetical = trail(last).contains(',') && words[i - 1].ends_with(',');
+        if !end.is_empty() || i == words.len() || parenthetical {
+            let prev = &mut words[i - 1];
+            let kept = prev.trim_end_matches([',', ';', ':']).len();
+            prev.truncate(kept);
+            if !ends_sentence(prev) {
+                prev.push_str(&end);
+            }
+        }
+    }
+
+    let capitalize = starts_sentence(words, i)
+        && first
+            .chars()
+            .find(|c| c.is_alphabetic())
+            .is_some_and(char::is_uppercase);
+    if let Some(next) = words.get_mut(i) {
+        let lead = &first[..first.len() - first.trim_start_matches(is_opening).len()];
+        let mut text = String::from(lead);
+        text.push_str(next);
+        *next = if capitalize {
+            capitalize_first(&text)
+        } else {
+            text
+        };
+    }
+}
+
+// "ummm" for "um", "эээ" for "э": the entry with its last letter drawn out
+fn is_drawn_out(word: &str, entry: &str) -> bool {
+    let Some(last) = entry.chars().last() else {
+        return false;
+    };
+    word.strip_prefix(entry)
+        .is_some_and(|rest| rest.chars().all(|c| c == last))
+}
+
+fn starts_sentence(words: &[String], i: usize) -> bool {
+    i == 0 || ends_sentence(&words[i - 1])
+}
+
+fn ends_sentence(word: &str) -> bool {
+    trail(word).contains(['.', '?', '!', '…'])
+}
+
+fn is_opening(c: char) -> bool {
+    matches!(c, '(' | '«' | '“' | '"' | '\'')
+}
+
+fn capitalize_first(text: &str) -> String {
+    match text.find(char::is_alphabetic) {
+        Some(pos) => {
+            let first = text[pos..].chars().next().unwrap_or_default();
+            format!(
+                "{}{}{}",
+                &text[..pos],
+                first.to_uppercase(),
+                &text[pos + first.len_utf8()..]
+            )
+        }
+        None => text.to_string(),
+    }
+}
+
+// The word inside surrounding punctuation: "Um," -> "Um"
+fn core(word: &str) -> &str {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+}
+
+// Punctuation after the word: "Um," -> ","
+fn trail(word: &str) -> &str {
+    let end = word.trim_end_matches(|c: char| !c.is_alphanumeric()).len();
+    &word[end..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(mode: &str, language: &str, cases: &[(&str, &str)]) {
+        let settings = Fillers {
+            mode: mode.to_string(),
+            ..Default::default()
+        };
+        let stage = FillerRemoval::for_language(&settings, language);
+        for (input, expected) in cases {
+            assert_eq!(stage.apply(input), *expected, "{} input: {:?}", mode, input);
+        }
+    }
+
+    #[test]
+    fn conservative() {
+        check(
+            "conservative",
+            "en",
+            &[
+                ("Um, I think, uh, it works.", "I think it works."),
+                ("I think, um.", "I think."),
+                ("Ummm the the cat", "The cat"),
+                ("call five five five", "call five five five"),
+                ("I know that that works", "I know that that works"),
+                ("Stop. Stop.", "Stop. Stop."),
+                ("You know, it works.", "You know, it works."),
+            ],
+        );
+        check(
+            "conservative",
+            "ru",
+            &[("Эээ, мы мы пошли.", "Мы пошли."), ("да да", "да да")],
+        );
+    }
+
+    #[test]
+    fn aggressive() {
+        check(
+            "aggressive",
+            "en",
+            &[
+                ("You know, it works.", "It works."),
+                ("I was, like, tired.", "I was tired."),
+                ("I think I think it works", "I think it works"),
+                ("Like I said, it works.", "Like I said, it works."),
+                ("I like it", "I like it"),
+                ("Well done.", "Well done."),
+            ],
+        );
+        check(
+            "aggressive",
+            "ru",
+            &[
+                ("Ну, короче, пошли.", "Пошли."),
+                ("Ну и ладно", "Ну и ладно"),
+            ],
+        );
+    }
+
+    #[test]
+    fn other_languages_unchanged() {
+        check("aggressive", "de", &[("äh also", "äh also")]);
+    }
+}
@@ -0,0 +1,60 @@
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt, Window};
+use x11rb::rust_connection::RustConnection;
+
+/// WM_CLASS of a window: instance and class name (e.g. "gnome-terminal-server", "Gnome-terminal")
+#[derive(Debug, Clone)]
+pub struct WindowClass {
+    pub instance: String,
+    pub class: String,
+}
+
+impl WindowClass {
+    /// Whether `name` is the instance or class name, ignoring case
+    pub fn matches(&self, name: &str) -> bool {
+        self.instance.eq_ignore_ascii_case(name) || self.class.eq_ignore_ascii_case(name)
+    }
+}
+
+fn active_window(conn: &RustConnection, screen: usize) -> Option<Window> {
+    let root = conn.setup().roots.get(screen)?.root;
+    let atom = conn
+        .intern_atom(true, b"_NET_ACTIVE_WINDOW")
+        .ok()?
+        .reply()
+        .ok()?
+        .atom;
+    let reply = conn
+        .get_property(false, root, atom, AtomEnum::WINDOW, 0, 1)
+        .ok()?
+        .reply()
+        .ok()?;
+    let id = reply.value32()?.next();
+    id.filter(|id| *id != 0)
+}
+
+/// X11 id of the active window (`_NET_ACTIVE_WINDOW`).
+/// None without an X server (pure Wayland) or a window manager that publishes it.
+pub fn active_window_id() -> Option<u32> {
+    let (conn, screen) = x11rb::connect(None).ok()?;
+    active_window(&conn, screen)
+}
+
+/// WM_CLASS of the active window (XWayland windows included)
+pub fn active_window_class() -> Option<WindowClass> {
+    let (conn, screen) = x11rb::connect(None).ok()?;
+    let window = active_window(&conn, screen)?;
+    let reply = conn
+        .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)
+        .ok()?
+        .reply()
+        .ok()?;
+    // Two NUL-terminated strings: instance, then class
+    let mut names = reply
+        .value
+        .split(|b| *b == 0)
+        .map(|name| String::from_utf8_lossy(name).into_owned());
+    let instance = names.next().filter(|name| !name.is_empty())?;
+    let class = names.next().unwrap_or_default();
+    Some(WindowClass { instance, class })
+}
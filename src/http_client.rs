@@ -0,0 +1,47 @@
+use reqwest::blocking::{Client, ClientBuilder};
+use reqwest::redirect::Policy;
+use reqwest::Proxy;
+use std::time::Duration;
+
+use crate::config;
+use crate::model_integrity;
+
+/// Build a blocking HTTP client honoring the configured proxy and offline mode
+pub fn blocking_client(timeout: Duration) -> Result<Client, String> {
+    build(Client::builder().timeout(timeout))
+}
+
+fn build(mut builder: ClientBuilder) -> Result<Client, String> {
+    if config::is_offline() {
+        return Err("Offline mode is enabled in config; network access is disabled".to_string());
+    }
+
+    if let Some(proxy_url) = config::get_proxy() {
+        let proxy = Proxy::all(&proxy_url)
+            .map_err(|e| format!("Invalid proxy URL '{}': {}", proxy_url, e))?;
+        builder = builder.proxy(proxy);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// SHA-256 the server publishes for the file at `url` (`X-Linked-Etag` or `ETag` of a HEAD request).
+/// Redirects are not followed: Hugging Face sends the checksum with the redirect to its CDN,
+/// whose own ETag is not a SHA-256.
+pub fn published_sha256(url: &str) -> Option<String> {
+    let client = build(
+        Client::builder()
+            .timeout(Duration::from_secs(30))
+            .redirect(Policy::none()),
+    )
+    .ok()?;
+    let response = client.head(url).send().ok()?;
+    ["x-linked-etag", "etag"].iter().find_map(|h| {
+        response
+            .headers()
+            .get(*h)
+            .and_then(|v| v.to_str().ok())
+            .and_then(model_integrity::sha256_from_etag)
+    })
+}
@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use crate::accessibility;
+use crate::clipboard_inserter;
+use crate::config::{self, AppRule, Insertion};
+use crate::focused_window;
+use crate::typing_inserter;
+
+// Insertion methods in the order they are tried after the configured one
+const METHODS: &[&str] = &["clipboard", "type", "atspi"];
+
+/// Why an insertion method failed
+#[derive(Debug)]
+pub enum InsertError {
+    /// Nothing was inserted; the next method can try
+    Failed(String),
+    /// Some or all of the text may have arrived; another method could insert it twice
+    Partial(String),
+}
+
+impl From<String> for InsertError {
+    fn from(e: String) -> Self {
+        InsertError::Failed(e)
+    }
+}
+
+/// The application a dictation goes to and how to insert into it
+pub struct Target {
+    /// Insertion settings with the application's rule applied
+    pub settings: Insertion,
+    /// Post-processing profile from the application's rule
+    pub profile: Option<String>,
+}
+
+impl Target {
+    /// Look up the rule for the focused application: by X11 WM_CLASS,
+    /// or else by the AT-SPI name of the application owning the active window
+    pub fn focused() -> Self {
+        let settings = config::get_insertion();
+        let rule = find_rule(&settings);
+        Self {
+            settings: settings.with_rule(&rule),
+            profile: rule.profile,
+        }
+    }
+
+    /// Insert `text` at the cursor with the method for this application.
+    /// The other methods are tried in turn if it fails.
+    pub fn insert(&self, text: &str) -> Result<(), String> {
+        let settings = &self.settings;
+        let first = match METHODS.iter().find(|m| **m == settings.method) {
+            Some(m) => *m,
+            None => {
+                eprintln!(
+                    "Unknown insertion method '{}', using clipboard",
+                    settings.method
+                );
+                "clipboard"
+            }
+        };
+        let order = std::iter::once(first).chain(METHODS.iter().copied().filter(|m| *m != first));
+
+        let mut errors = Vec::new();
+        for method in order {
+            let result = match method {
+                "atspi" => accessibility::insert_at_caret(text),
+                "type" => {
+                    clipboard_inserter::release_modifiers();
+                    typing_inserter::type_text(text, Duration::from_millis(settings.type_delay_ms))
+                }
+                _ => clipboard_inserter::insert_text(text, settings).map_err(InsertError::from),
+            };
+            match result {
+                Ok(()) => return Ok(()),
+                Err(InsertError::Failed(e)) => {
+                    eprintln!("Insertion via {} failed: {}", method, e);
+                    errors.push(e);
+                }
+                Err(InsertError::Partial(e)) => {
+                    return Err(format!("Insertion via {} stopped partway: {}", method, e));
+                }
+            }
+        }
+        Err(format!("Could not insert text: {}", errors.join("; ")))
+    }
+}
+
+fn find_rule(settings: &Insertion) -> AppRule {
+    if settings.apps.is_empty() {
+        return AppRule::default();
+    }
+    let by_class = focused_window::active_window_class().and_then(|class| {
+        settings
+            .apps
+            .iter()
+            .find(|(name, _)| class.matches(name))
+            .map(|(name, rule)| (name.clone(), rule.clone()))
+    });
+    // Wayland-native windows have no WM_CLASS; AT-SPI still names their application
+    let found = by_class.or_else(|| {
+        let app = accessibility::focused_application_name()?;
+        settings
+            .apps
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&app))
+            .map(|(name, rule)| (name.clone(), rule.clone()))
+    });
+    match found {
+        Some((name, rule)) => {
+            println!("Using the insertion rule for '{}'", name);
+            rule
+        }
+        None => AppRule::default(),
+    }
+}
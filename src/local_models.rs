@@ -0,0 +1,151 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::model_registry;
+
+/// ggml file magic ("ggml" as a little-endian u32), as written by whisper.cpp
+const GGML_MAGIC: u32 = 0x6767_6d6c;
+
+/// Hyperparameters read from the header of a ggml whisper model
+#[derive(Debug, Clone, Copy)]
+pub struct GgmlHeader {
+    pub n_vocab: i32,
+    pub n_audio_layer: i32,
+    pub n_text_layer: i32,
+    pub n_mels: i32,
+    pub ftype: i32,
+}
+
+impl GgmlHeader {
+    /// whisper.cpp treats vocabularies smaller than the multilingual one as English-only
+    pub fn is_multilingual(&self) -> bool {
+        self.n_vocab >= 51865
+    }
+}
+
+/// A user-supplied model file that passed validation
+#[derive(Debug, Clone)]
+pub struct LocalModel {
+    pub name: String,
+    pub path: PathBuf,
+    pub english_only: bool,
+}
+
+/// Read and sanity-check the header of a ggml whisper model file.
+/// This catches wrong files (other formats, truncated downloads) without loading the weights.
+pub fn inspect_model_file(path: &Path) -> Result<GgmlHeader, String> {
+    let mut file = File::open(path)
+        .map_err(|e| format!("Failed to open model file {}: {}", path.display(), e))?;
+
+    // magic + 11 i32 hyperparameters
+    let mut buf = [0u8; 48];
+    file.read_exact(&mut buf)
+        .map_err(|e| format!("Model file {} is too short: {}", path.display(), e))?;
+    let field = |i: usize| i32::from_le_bytes([buf[i * 4], buf[i * 4 + 1], buf[i * 4 + 2], buf[i * 4 + 3]]);
+
+    let magic = field(0) as u32;
+    if magic != GGML_MAGIC {
+        return Err(format!(
+            "{} is not a ggml whisper model (bad magic 0x{:08x})",
+            path.display(),
+            magic
+        ));
+    }
+
+    let header = GgmlHeader {
+        n_vocab: field(1),
+        n_audio_layer: field(5),
+        n_text_layer: field(9),
+        n_mels: field(10),
+        ftype: field(11),
+    };
+    let plausible = header.n_vocab > 50000
+        && header.n_audio_layer > 0
+        && header.n_text_layer > 0
+        && (header.n_mels == 80 || header.n_mels == 128);
+    if !plausible {
+        return Err(format!(
+            "{} has an unexpected whisper header: {:?}",
+            path.display(),
+            header
+        ));
+    }
+    Ok(header)
+}
+
+/// User-supplied models from config whose files exist and look loadable
+pub fn available_custom_models() -> Vec<LocalModel> {
+    let mut models = Vec::new();
+    for custom in config::get_custom_models() {
+        let path = PathBuf::from(&custom.path);
+        match inspect_model_file(&path) {
+            Ok(header) => models.push(LocalModel {
+                name: custom.name,
+                path,
+                english_only: !header.is_multilingual(),
+            }),
+            Err(e) => eprintln!("Skipping custom model '{}': {}", custom.name, e),
+        }
+    }
+    models
+}
+
+/// Validate a file and register it in config under `name`
+pub fn register_custom_model(name: &str, path: &Path) -> Result<LocalModel, String> {
+    if model_registry::find(name).is_some() {
+        return Err(format!("'{}' is already the name of a built-in model", name));
+    }
+    let path = path
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", path.display(), e))?;
+    let header = inspect_model_file(&path)?;
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| format!("Invalid UTF-8 in model path: {:?}", path))?;
+    config::save_custom_model(name, path_str)
+        .map_err(|e| format!("Failed to save custom model to config: {}", e))?;
+    Ok(LocalModel {
+        name: name.to_string(),
+        path,
+        english_only: !header.is_multilingual(),
+    })
+}
+
+/// Default name for a model file: its stem without the "ggml-" prefix
+pub fn default_model_name(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "custom".to_string());
+    stem.strip_prefix("ggml-").unwrap_or(&stem).to_string()
+}
+
+/// File names used for English and for other languages when `selected` is chosen.
+/// Custom models are referenced by absolute path; an English-only custom model
+/// falls back to the base multilingual model for other languages.
+pub fn model_files(selected: &str) -> (String, String) {
+    if let Some(custom) = config::get_custom_models()
+        .into_iter()
+        .find(|m| m.name == selected)
+    {
+        let english_only = inspect_model_file(Path::new(&custom.path))
+            .map(|h| !h.is_multilingual())
+            .unwrap_or(false);
+        return if english_only {
+            (
+                custom.path,
+                model_registry::FALLBACK_MULTILINGUAL_FILE.to_string(),
+            )
+        } else {
+            (custom.path.clone(), custom.path)
+        };
+    }
+    model_registry::model_files(selected)
+}
+
+/// Whether `selected` names a user-supplied model (never downloaded)
+pub fn is_custom(selected: &str) -> bool {
+    config::get_custom_models().iter().any(|m| m.name == selected)
+}
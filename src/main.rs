@@ -5,21 +5,54 @@ use std::sync::mpsc::{channel, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
 
+mod accessibility;
+mod api_server;
 mod app;
 mod audio_stream;
+mod audio_utils;
+mod bench;
+mod caret_context;
+mod cli;
 mod clipboard_inserter;
+mod compute_policy;
 mod config;
+mod download_manager;
+mod eval;
+mod fillers;
+mod focused_window;
 mod hotkeys;
+mod http_client;
+mod insertion;
 mod keyboard_layout;
+mod local_models;
+mod model_cache;
+mod model_integrity;
+mod model_manager;
+mod model_registry;
+mod post_processing;
+mod prompt_context;
+mod remote_transcriber;
+mod replacements;
 mod single_instance;
+mod spoken_commands;
+mod text_normalization;
+mod transcriber;
 mod transcriber_utils;
 mod tray_ui;
+mod typing_inserter;
 mod whisper;
+mod wyoming_server;
 
 use audio_stream::AudioStream;
 use hotkeys::{handle_keyboard_event, KeyboardEvent, KEYBOARD_EVENT_SENDER, init_hotkeys_from_config};
 
 fn main() {
+    // Subcommands (e.g. `voice_input models list`) run and exit without starting the app
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
     // keep the lock alive for the entire program
     let _instance_lock = single_instance::ensure_single_instance();
 
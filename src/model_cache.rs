@@ -0,0 +1,246 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config;
+use crate::whisper::WhisperTranscriber;
+
+/// Identifies a loaded whisper context: the model file and the device it runs on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModelKey {
+    pub model_file: String,
+    pub device: String,
+}
+
+impl ModelKey {
+    /// Key for `model_file` on the currently configured compute device
+    pub fn for_current_device(model_file: &str) -> Self {
+        Self {
+            model_file: model_file.to_string(),
+            device: config::get_device(),
+        }
+    }
+
+    fn is_gpu(&self) -> bool {
+        self.device == "gpu"
+    }
+}
+
+/// Limits and behavior of the model cache (read from config)
+#[derive(Debug, Clone)]
+pub struct CacheSettings {
+    /// Maximum total size of models kept in RAM (CPU contexts), in bytes. 0 = unlimited.
+    pub ram_budget_bytes: u64,
+    /// Maximum total size of models kept in VRAM (GPU contexts), in bytes. 0 = unlimited.
+    pub vram_budget_bytes: u64,
+    /// Unload models that were not used for this long. None = keep until evicted by budget.
+    pub idle_timeout: Option<Duration>,
+    /// Run a short inference on silence right after loading to warm up the context
+    pub warm_up: bool,
+}
+
+impl CacheSettings {
+    pub fn from_config() -> Self {
+        let cfg = config::load_config();
+        Self {
+            ram_budget_bytes: cfg.model_ram_budget_mb * 1024 * 1024,
+            vram_budget_bytes: cfg.model_vram_budget_mb * 1024 * 1024,
+            idle_timeout: if cfg.model_idle_unload_secs > 0 {
+                Some(Duration::from_secs(cfg.model_idle_unload_secs))
+            } else {
+                None
+            },
+            warm_up: cfg.model_warm_up,
+        }
+    }
+}
+
+struct CacheEntry {
+    key: ModelKey,
+    transcriber: Arc<WhisperTranscriber>,
+    size_bytes: u64,
+    last_used: Instant,
+}
+
+/// Cache of loaded whisper contexts keyed by model file and device.
+///
+/// Entries are evicted least-recently-used first when a device budget is exceeded,
+/// and unloaded after the idle timeout. Entries currently borrowed by a transcription
+/// are never evicted.
+pub struct ModelCache {
+    settings: Mutex<CacheSettings>,
+    entries: Mutex<Vec<CacheEntry>>,
+    // Serializes loads so the same model is never loaded twice concurrently
+    load_lock: Mutex<()>,
+}
+
+impl ModelCache {
+    pub fn new(settings: CacheSettings) -> Self {
+        Self {
+            settings: Mutex::new(settings),
+            entries: Mutex::new(Vec::new()),
+            load_lock: Mutex::new(()),
+        }
+    }
+
+    /// Replace cache limits (e.g. after the config changed) and apply them immediately
+    pub fn update_settings(&self, settings: CacheSettings) {
+        *self.settings.lock().unwrap() = settings;
+        self.enforce_budget(None);
+        self.evict_idle();
+    }
+
+    /// Whether a context for `key` is loaded
+    pub fn contains(&self, key: &ModelKey) -> bool {
+        self.entries.lock().unwrap().iter().any(|e| e.key == *key)
+    }
+
+    /// Get a loaded context and mark it as recently used
+    pub fn get(&self, key: &ModelKey) -> Option<Arc<WhisperTranscriber>> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.iter_mut().find(|e| e.key == *key)?;
+        entry.last_used = Instant::now();
+        Some(entry.transcriber.clone())
+    }
+
+    /// Get a loaded context, loading it (and evicting others as needed) if missing
+    pub fn get_or_load(&self, key: &ModelKey) -> Result<Arc<WhisperTranscriber>, String> {
+        if let Some(t) = self.get(key) {
+            return Ok(t);
+        }
+
+        let _load_guard = self.load_lock.lock().unwrap();
+        // Another thread may have loaded it while we waited
+        if let Some(t) = self.get(key) {
+            return Ok(t);
+        }
+
+        let size_bytes = config::get_model_path(&key.model_file)
+            .and_then(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len())
+            .unwrap_or(0);
+
+        // Make room before loading so peak usage stays within budget
+        self.enforce_budget(Some((key, size_bytes)));
+
+        let transcriber = WhisperTranscriber::new_on_device(&key.model_file, key.is_gpu())?;
+        if self.settings.lock().unwrap().warm_up {
+            transcriber.warm_up();
+        }
+        let transcriber = Arc::new(transcriber);
+
+        self.entries.lock().unwrap().push(CacheEntry {
+            key: key.clone(),
+            transcriber: transcriber.clone(),
+            size_bytes,
+            last_used: Instant::now(),
+        });
+        Ok(transcriber)
+    }
+
+    /// Unload contexts that have been idle longer than the configured timeout
+    pub fn evict_idle(&self) {
+        let timeout = match self.settings.lock().unwrap().idle_timeout {
+            Some(t) => t,
+            None => return,
+        };
+        let now = Instant::now();
+        self.evict_where(|e| now.duration_since(e.last_used) >= timeout, "idle timeout");
+    }
+
+    /// Unload every context that is not currently in use
+    pub fn clear(&self) {
+        self.evict_where(|_| true, "cache cleared");
+    }
+
+    // Evict LRU entries on the device of `incoming` (or all devices) until within budget
+    fn enforce_budget(&self, incoming: Option<(&ModelKey, u64)>) {
+        let settings = self.settings.lock().unwrap().clone();
+        for gpu in [false, true] {
+            if let Some((key, _)) = incoming {
+                if key.is_gpu() != gpu {
+                    continue;
+                }
+            }
+            let budget = if gpu {
+                settings.vram_budget_bytes
+            } else {
+                settings.ram_budget_bytes
+            };
+            if budget == 0 {
+                continue;
+            }
+            let extra = incoming.map(|(_, size)| size).unwrap_or(0);
+
+            let mut evicted = Vec::new();
+            {
+                let mut entries = self.entries.lock().unwrap();
+                loop {
+                    let used: u64 = entries
+                        .iter()
+                        .filter(|e| e.key.is_gpu() == gpu)
+                        .map(|e| e.size_bytes)
+                        .sum();
+                    if used + extra <= budget {
+                        break;
+                    }
+                    // Least recently used entry on this device that nobody is borrowing
+                    let victim = entries
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, e)| {
+                            e.key.is_gpu() == gpu && Arc::strong_count(&e.transcriber) == 1
+                        })
+                        .min_by_key(|(_, e)| e.last_used)
+                        .map(|(i, _)| i);
+                    match victim {
+                        Some(i) => evicted.push(entries.remove(i)),
+                        None => break,
+                    }
+                }
+            }
+            self.release(evicted, "memory budget");
+        }
+    }
+
+    fn evict_where<F: Fn(&CacheEntry) -> bool>(&self, pred: F, reason: &str) {
+        let mut evicted = Vec::new();
+        {
+            let mut entries = self.entries.lock().unwrap();
+            let mut i = 0;
+            while i < entries.len() {
+                if pred(&entries[i]) && Arc::strong_count(&entries[i].transcriber) == 1 {
+                    evicted.push(entries.remove(i));
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        self.release(evicted, reason);
+    }
+
+    // Drop evicted contexts outside of the entries lock
+    fn release(&self, evicted: Vec<CacheEntry>, reason: &str) {
+        if evicted.is_empty() {
+            return;
+        }
+        #[allow(unused_variables)]
+        let freed_gpu = evicted.iter().any(|e| e.key.is_gpu());
+        for entry in evicted {
+            println!(
+                "Unloading model {} ({}) from cache: {}",
+                entry.key.model_file, entry.key.device, reason
+            );
+            // Dropping WhisperTranscriber drops WhisperContext and releases associated memory/VRAM.
+            drop(entry);
+        }
+
+        // When the last GPU context is gone, reset the device to reclaim VRAM held by CUDA caches
+        #[cfg(feature = "cuda")]
+        {
+            let gpu_left = self.entries.lock().unwrap().iter().any(|e| e.key.is_gpu());
+            if freed_gpu && !gpu_left {
+                WhisperTranscriber::free_cuda_vram();
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::local_models;
+use crate::model_registry;
+
+/// Path of the in-progress download for a model file
+pub fn part_path(model_path: &Path) -> PathBuf {
+    append_extension(model_path, "part")
+}
+
+/// Path of the sidecar that records the verified checksum and size of a model file
+pub fn checksum_path(model_path: &Path) -> PathBuf {
+    append_extension(model_path, "sha256")
+}
+
+fn append_extension(path: &Path, ext: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(ext);
+    PathBuf::from(name)
+}
+
+/// Compute the lowercase hex SHA-256 of a file
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file =
+        File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let n = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Parse a SHA-256 advertised by a mirror (ETag / X-Linked-Etag), e.g. `"abc..."` or `W/"abc..."`
+pub fn sha256_from_etag(etag: &str) -> Option<String> {
+    let value = etag.trim().trim_start_matches("W/").trim_matches('"');
+    if value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(value.to_ascii_lowercase())
+    } else {
+        None
+    }
+}
+
+/// Record the verified checksum and size next to a model file
+pub fn write_checksum(model_path: &Path, sha256: &str, size: u64) -> Result<(), String> {
+    fs::write(checksum_path(model_path), format!("{}  {}\n", sha256, size))
+        .map_err(|e| format!("Failed to write checksum file: {}", e))
+}
+
+/// Read the recorded (sha256, size) for a model file, if any
+pub fn read_checksum(model_path: &Path) -> Option<(String, u64)> {
+    let contents = fs::read_to_string(checksum_path(model_path)).ok()?;
+    let mut parts = contents.split_whitespace();
+    let sha = parts.next()?.to_string();
+    let size = parts.next()?.parse().ok()?;
+    Some((sha, size))
+}
+
+/// Cheap load-time check that a model file is complete and well-formed:
+/// the size recorded at download time must match and the ggml header must be valid.
+/// Files downloaded before checksums were recorded are compared to the registry size instead.
+pub fn verify_model_file(path: &Path) -> Result<(), String> {
+    let len = fs::metadata(path)
+        .map_err(|e| format!("Failed to stat {}: {}", path.display(), e))?
+        .len();
+
+    match read_checksum(path) {
+        Some((_, size)) if size != len => {
+            return Err(format!(
+                "{} is {} bytes but {} bytes were downloaded",
+                path.display(),
+                len,
+                size
+            ));
+        }
+        Some(_) => {}
+        None => {
+            // Legacy download: catch obvious truncation against the approximate registry size
+            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if let Some(info) = model_registry::find_by_file(file_name) {
+                let expected = info.size_mb * 1024 * 1024;
+                if len < expected / 2 {
+                    return Err(format!(
+                        "{} is {} bytes, expected about {} MB (truncated download?)",
+                        path.display(),
+                        len,
+                        info.size_mb
+                    ));
+                }
+            }
+        }
+    }
+
+    local_models::inspect_model_file(path).map(|_| ())
+}
+
+/// Remove a model file together with its checksum sidecar and any partial download
+pub fn remove_model_file(path: &Path) -> Result<(), String> {
+    for p in [path.to_path_buf(), checksum_path(path), part_path(path)] {
+        if p.exists() {
+            fs::remove_file(&p).map_err(|e| format!("Failed to remove {}: {}", p.display(), e))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config;
+use crate::local_models;
+use crate::model_integrity;
+use crate::model_registry::{self, ModelInfo};
+
+/// What is on disk for a registry model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelState {
+    Missing,
+    /// An interrupted download (`.part` file) that will be resumed
+    Partial,
+    Present,
+    /// The file exists but fails the size/header checks
+    Corrupt(String),
+}
+
+impl ModelState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ModelState::Missing => "missing",
+            ModelState::Partial => "partial",
+            ModelState::Present => "present",
+            ModelState::Corrupt(_) => "corrupt",
+        }
+    }
+}
+
+/// A registry model together with its local state
+#[derive(Debug, Clone)]
+pub struct ModelStatus {
+    pub info: &'static ModelInfo,
+    pub state: ModelState,
+    /// The model file, or the partial download for `Partial`
+    pub path: Option<PathBuf>,
+    pub size_on_disk: u64,
+    pub last_used: Option<SystemTime>,
+}
+
+// Last-used times are kept next to the models as { "file name": unix seconds }
+fn usage_file_path() -> Option<PathBuf> {
+    config::get_data_dir().map(|dir| dir.join("model_usage.json"))
+}
+
+fn load_usage() -> HashMap<String, u64> {
+    usage_file_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Remember that a model file was just used for transcription
+pub fn record_model_use(model_file: &str) {
+    let Some(path) = usage_file_path() else {
+        return;
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut usage = load_usage();
+    usage.insert(model_file.to_string(), now);
+    let result = serde_json::to_string_pretty(&usage)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            fs::write(&path, json).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        eprintln!("Failed to record model usage: {}", e);
+    }
+}
+
+/// State, size and last use of one registry model
+pub fn model_status(info: &'static ModelInfo, usage: &HashMap<String, u64>) -> ModelStatus {
+    let last_used = usage
+        .get(info.file_name)
+        .map(|secs| UNIX_EPOCH + Duration::from_secs(*secs));
+
+    if let Some(path) = config::get_model_path(info.file_name) {
+        let size_on_disk = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let state = match model_integrity::verify_model_file(&path) {
+            Ok(()) => ModelState::Present,
+            Err(e) => ModelState::Corrupt(e),
+        };
+        return ModelStatus {
+            info,
+            state,
+            path: Some(path),
+            size_on_disk,
+            last_used,
+        };
+    }
+
+    let part = config::get_models_dir()
+        .map(|dir| model_integrity::part_path(&dir.join(info.file_name)))
+        .filter(|p| p.exists());
+    match part {
+        Some(part) => ModelStatus {
+            info,
+            state: ModelState::Partial,
+            size_on_disk: fs::metadata(&part).map(|m| m.len()).unwrap_or(0),
+            path: Some(part),
+            last_used,
+        },
+        None => ModelStatus {
+            info,
+            state: ModelState::Missing,
+            path: None,
+            size_on_disk: 0,
+            last_used,
+        },
+    }
+}
+
+/// All registry models (including English-only and quantized files) with their local state
+pub fn list_models() -> Vec<ModelStatus> {
+    let usage = load_usage();
+    model_registry::MODELS
+        .iter()
+        .map(|info| model_status(info, &usage))
+        .collect()
+}
+
+/// Whether the file of `model_id` is used by the currently selected model
+pub fn is_in_use(model_id: &str) -> bool {
+    let Some(info) = model_registry::find(model_id) else {
+        return false;
+    };
+    let (en, multi) = local_models::model_files(&config::get_selected_model());
+    en == info.file_name || multi == info.file_name
+}
+
+/// Delete the files of a registry model (model, checksum sidecar and partial download).
+/// Refuses to delete the model that is currently selected.
+pub fn delete_model(model_id: &str) -> Result<u64, String> {
+    let info = model_registry::find(model_id)
+        .ok_or_else(|| format!("Unknown model '{}'", model_id))?;
+    if is_in_use(model_id) {
+        return Err(format!(
+            "'{}' is used by the active model; select another model before deleting it",
+            model_id
+        ));
+    }
+
+    let status = model_status(info, &load_usage());
+    if status.state == ModelState::Missing {
+        return Err(format!("Model '{}' is not downloaded", model_id));
+    }
+    let model_path = match status.state {
+        // The partial download sits next to where the model file would be
+        ModelState::Partial => config::get_models_dir()
+            .map(|dir| dir.join(info.file_name))
+            .ok_or("Could not determine models directory")?,
+        _ => status.path.clone().unwrap_or_default(),
+    };
+    model_integrity::remove_model_file(&model_path)?;
+    println!("Deleted model '{}' ({})", model_id, model_path.display());
+    Ok(status.size_on_disk)
+}
+
+/// Remove a model's files so the next download starts from scratch (used for re-downloads)
+pub fn discard_model_files(model_id: &str) -> Result<(), String> {
+    let info = model_registry::find(model_id)
+        .ok_or_else(|| format!("Unknown model '{}'", model_id))?;
+    if let Some(path) = config::get_model_path(info.file_name) {
+        model_integrity::remove_model_file(&path)?;
+    }
+    if let Some(dir) = config::get_models_dir() {
+        model_integrity::remove_model_file(&dir.join(info.file_name))?;
+    }
+    Ok(())
+}
+
+/// Human readable size, e.g. "466 MB" or "1.5 GB"
+pub fn format_size(bytes: u64) -> String {
+    let mb = bytes as f64 / (1024.0 * 1024.0);
+    if mb >= 1024.0 {
+        format!("{:.1} GB", mb / 1024.0)
+    } else {
+        format!("{:.0} MB", mb)
+    }
+}
+
+/// Local date and time of the last use, or "never"
+pub fn format_last_used(last_used: Option<SystemTime>) -> String {
+    match last_used {
+        Some(time) => chrono::DateTime::<chrono::Local>::from(time)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => "never".to_string(),
+    }
+}
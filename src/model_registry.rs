@@ -0,0 +1,149 @@
+/// A whisper.cpp ggml model file known to the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelInfo {
+    /// Identifier used in config and UI, e.g. "small", "small.en", "large-v3-turbo-q5_0"
+    pub id: &'static str,
+    /// File name in the models directory and on the download mirror
+    pub file_name: &'static str,
+    /// Whether the model only transcribes English
+    pub english_only: bool,
+    /// Approximate download size in MB
+    pub size_mb: u64,
+    /// Pinned SHA-256 of the file, when known
+    pub sha256: Option<&'static str>,
+    /// For English-only models: id of the multilingual model with the same size/quantization
+    pub multilingual: Option<&'static str>,
+}
+
+impl ModelInfo {
+    /// The same entry with its SHA-256 pinned (written by `scripts/update_model_checksums.py`)
+    #[allow(dead_code)]
+    const fn pinned(self, sha256: &'static str) -> ModelInfo {
+        ModelInfo {
+            sha256: Some(sha256),
+            ..self
+        }
+    }
+
+    /// Whether the weights are quantized (q5/q8 variants)
+    pub fn is_quantized(&self) -> bool {
+        self.id.contains("-q")
+    }
+}
+
+/// Model used when the configured selection is unknown
+pub const DEFAULT_MODEL: &str = "small";
+
+/// Smallest models, used when the selected model file is missing
+pub const FALLBACK_ENGLISH_FILE: &str = "ggml-base.en.bin";
+pub const FALLBACK_MULTILINGUAL_FILE: &str = "ggml-base.bin";
+
+const fn model(id: &'static str, file_name: &'static str, size_mb: u64) -> ModelInfo {
+    ModelInfo {
+        id,
+        file_name,
+        english_only: false,
+        size_mb,
+        sha256: None,
+        multilingual: None,
+    }
+}
+
+const fn english(
+    id: &'static str,
+    file_name: &'static str,
+    size_mb: u64,
+    multilingual: &'static str,
+) -> ModelInfo {
+    ModelInfo {
+        id,
+        file_name,
+        english_only: true,
+        size_mb,
+        sha256: None,
+        multilingual: Some(multilingual),
+    }
+}
+
+/// All models published in the upstream whisper.cpp model repository that the app supports.
+/// Checksums are pinned with `.pinned(...)` by `scripts/update_model_checksums.py`;
+/// entries without one are verified against the checksum published upstream at download time.
+pub static MODELS: &[ModelInfo] = &[
+    model("tiny", "ggml-tiny.bin", 75),
+    english("tiny.en", "ggml-tiny.en.bin", 75, "tiny"),
+    model("base", "ggml-base.bin", 142),
+    english("base.en", "ggml-base.en.bin", 142, "base"),
+    model("small", "ggml-small.bin", 466),
+    english("small.en", "ggml-small.en.bin", 466, "small"),
+    model("medium", "ggml-medium.bin", 1533),
+    english("medium.en", "ggml-medium.en.bin", 1533, "medium"),
+    model("large-v1", "ggml-large-v1.bin", 2951),
+    model("large-v2", "ggml-large-v2.bin", 2951),
+    model("large-v3", "ggml-large-v3.bin", 2952),
+    model("large-v3-turbo", "ggml-large-v3-turbo.bin", 1549),
+    // Quantized variants
+    model("tiny-q5_1", "ggml-tiny-q5_1.bin", 31),
+    english("tiny.en-q5_1", "ggml-tiny.en-q5_1.bin", 31, "tiny-q5_1"),
+    model("tiny-q8_0", "ggml-tiny-q8_0.bin", 42),
+    english("tiny.en-q8_0", "ggml-tiny.en-q8_0.bin", 42, "tiny-q8_0"),
+    model("base-q5_1", "ggml-base-q5_1.bin", 57),
+    english("base.en-q5_1", "ggml-base.en-q5_1.bin", 57, "base-q5_1"),
+    model("base-q8_0", "ggml-base-q8_0.bin", 78),
+    english("base.en-q8_0", "ggml-base.en-q8_0.bin", 78, "base-q8_0"),
+    model("small-q5_1", "ggml-small-q5_1.bin", 181),
+    english("small.en-q5_1", "ggml-small.en-q5_1.bin", 181, "small-q5_1"),
+    model("small-q8_0", "ggml-small-q8_0.bin", 252),
+    english("small.en-q8_0", "ggml-small.en-q8_0.bin", 252, "small-q8_0"),
+    model("medium-q5_0", "ggml-medium-q5_0.bin", 514),
+    english("medium.en-q5_0", "ggml-medium.en-q5_0.bin", 514, "medium-q5_0"),
+    model("medium-q8_0", "ggml-medium-q8_0.bin", 785),
+    english("medium.en-q8_0", "ggml-medium.en-q8_0.bin", 785, "medium-q8_0"),
+    model("large-v2-q5_0", "ggml-large-v2-q5_0.bin", 1080),
+    model("large-v2-q8_0", "ggml-large-v2-q8_0.bin", 1500),
+    model("large-v3-q5_0", "ggml-large-v3-q5_0.bin", 1080),
+    model("large-v3-turbo-q5_0", "ggml-large-v3-turbo-q5_0.bin", 547),
+    model("large-v3-turbo-q8_0", "ggml-large-v3-turbo-q8_0.bin", 834),
+];
+
+/// Look up a model by id
+pub fn find(id: &str) -> Option<&'static ModelInfo> {
+    MODELS.iter().find(|m| m.id == id)
+}
+
+/// Look up a model by its file name
+pub fn find_by_file(file_name: &str) -> Option<&'static ModelInfo> {
+    MODELS.iter().find(|m| m.file_name == file_name)
+}
+
+/// Models that can be selected in config/UI: the multilingual entries.
+/// English-only files are used automatically for English when a counterpart exists.
+pub fn selectable_models() -> impl Iterator<Item = &'static ModelInfo> {
+    MODELS.iter().filter(|m| !m.english_only)
+}
+
+/// Whether `id` is a valid model selection
+pub fn is_selectable(id: &str) -> bool {
+    selectable_models().any(|m| m.id == id)
+}
+
+/// The English-only counterpart of a selectable model, if one is published
+pub fn english_variant(id: &str) -> Option<&'static ModelInfo> {
+    MODELS
+        .iter()
+        .find(|m| m.english_only && m.multilingual == Some(id))
+}
+
+/// File names used for English and for other languages when `id` is selected.
+/// Both are the same file for models without an English-only variant (e.g. large).
+pub fn model_files(id: &str) -> (String, String) {
+    match find(id).filter(|m| !m.english_only) {
+        Some(m) => {
+            let en = english_variant(m.id).unwrap_or(m);
+            (en.file_name.to_string(), m.file_name.to_string())
+        }
+        None => (
+            FALLBACK_ENGLISH_FILE.to_string(),
+            FALLBACK_MULTILINGUAL_FILE.to_string(),
+        ),
+    }
+}
@@ -0,0 +1,284 @@
+use crate::config::{self, PostProcessing, TextRules};
+use crate::fillers::FillerRemoval;
+use crate::replacements::Replacements;
+use crate::spoken_commands::{self, SpokenCommands};
+use crate::text_normalization::InverseNormalization;
+
+/// One step of post-processing between decoding and insertion
+pub trait TextStage {
+    fn apply(&self, text: &str) -> String;
+}
+
+/// Ordered stages configured for one language
+pub struct Pipeline {
+    stages: Vec<Box<dyn TextStage>>,
+}
+
+impl Pipeline {
+    /// Build the stages of `profile` (or the default ones) for `language` (2-letter code).
+    /// Unknown stage names are skipped.
+    pub fn new(settings: &PostProcessing, language: &str, profile: Option<&str>) -> Self {
+        let rules = settings.rules_for(language);
+        let stages = settings
+            .stages_for(profile)
+            .iter()
+            .filter_map(|name| {
+                let stage = build_stage(name, settings, language, &rules);
+                if stage.is_none() {
+                    eprintln!("Unknown post-processing stage '{}'", name);
+                }
+                stage
+            })
+            .collect();
+        Self { stages }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        self.stages
+            .iter()
+            .fold(text.to_string(), |text, stage| stage.apply(&text))
+    }
+}
+
+fn build_stage(
+    name: &str,
+    settings: &PostProcessing,
+    language: &str,
+    rules: &TextRules,
+) -> Option<Box<dyn TextStage>> {
+    let stage: Box<dyn TextStage> = match name {
+        "whitespace" => Box::new(Whitespace),
+        "join_segments" => Box::new(JoinSegments {
+            separator: rules.segment_separator.clone(),
+        }),
+        "fillers" => Box::new(FillerRemoval::for_language(&settings.fillers, language)),
+        "replacements" => Box::new(Replacements::for_language(language)),
+        "numbers" => Box::new(InverseNormalization::for_language(language)),
+        "spoken_punctuation" => {
+            let extra = settings
+                .spoken_commands
+                .get(language.get(..2).unwrap_or(language))
+                .cloned()
+                .unwrap_or_default();
+            Box::new(SpokenCommands::new(spoken_commands::commands_for(
+                language, &extra,
+            )))
+        }
+        "trailing_punctuation" => Box::new(TrailingPunctuation {
+            policy: rules.trailing_punctuation.clone(),
+        }),
+        "capitalization" => Box::new(Capitalization {
+            policy: rules.capitalization.clone(),
+        }),
+        "trailing_space" => Box::new(TrailingSpace {
+            enabled: rules.trailing_space,
+        }),
+        _ => return None,
+    };
+    Some(stage)
+}
+
+/// Post-process decoded text for insertion with the configured pipeline.
+/// `profile` (e.g. from an application rule) takes precedence over the configured profile.
+pub fn process(text: &str, language: &str, profile: Option<&str>) -> String {
+    let settings = config::get_post_processing();
+    let profile = profile.or(settings.profile.as_deref());
+    Pipeline::new(&settings, language, profile).apply(text)
+}
+
+/// Trim every line, collapse runs of spaces and tabs, drop blank lines
+pub struct Whitespace;
+
+impl TextStage for Whitespace {
+    fn apply(&self, text: &str) -> String {
+        text.lines()
+            .map(|line| {
+                line.split(|c: char| c.is_whitespace())
+                    .filter(|w| !w.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Join whisper segments (one per line) with `separator`
+pub struct JoinSegments {
+    pub separator: String,
+}
+
+impl TextStage for JoinSegments {
+    fn apply(&self, text: &str) -> String {
+        text.lines().collect::<Vec<_>>().join(&self.separator)
+    }
+}
+
+/// "keep"; "strip" removes a final period, comma or ellipsis ("?" and "!" stay);
+/// "ensure" adds a period when the text doesn't end with sentence punctuation
+pub struct TrailingPunctuation {
+    pub policy: String,
+}
+
+impl TextStage for TrailingPunctuation {
+    fn apply(&self, text: &str) -> String {
+        let body = text.trim_end();
+        match self.policy.as_str() {
+            "strip" => body.trim_end_matches(['.', ',', '…']).to_string(),
+            "ensure" if !body.is_empty() && !body.ends_with(['.', '!', '?', '…', ':', ';']) => {
+                format!("{}.", body)
+            }
+            _ => text.to_string(),
+        }
+    }
+}
+
+/// First letter of the text: "keep", "upper" or "lower"
+pub struct Capitalization {
+    pub policy: String,
+}
+
+impl TextStage for Capitalization {
+    fn apply(&self, text: &str) -> String {
+        let Some(pos) = text.find(char::is_alphabetic) else {
+            return text.to_string();
+        };
+        let first = text[pos..].chars().next().unwrap_or_default();
+        let replaced: String = match self.policy.as_str() {
+            "upper" => first.to_uppercase().collect(),
+            // Keep acronyms and "I" as they are
+            "lower" if !is_acronym_start(&text[pos..]) => first.to_lowercase().collect(),
+            _ => return text.to_string(),
+        };
+        format!(
+            "{}{}{}",
+            &text[..pos],
+            replaced,
+            &text[pos + first.len_utf8()..]
+        )
+    }
+}
+
+/// "NASA ..." or "I ..." at the start of `text`
+pub fn is_acronym_start(text: &str) -> bool {
+    let word: Vec<char> = text.chars().take_while(|c| c.is_alphanumeric()).collect();
+    word == ['I'] || (word.len() > 1 && word.iter().all(|c| !c.is_lowercase()))
+}
+
+/// Append a space so the next dictation doesn't run into this one
+pub struct TrailingSpace {
+    pub enabled: bool,
+}
+
+impl TextStage for TrailingSpace {
+    fn apply(&self, text: &str) -> String {
+        if self.enabled && !text.is_empty() && !text.ends_with(char::is_whitespace) {
+            format!("{} ", text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn check(stage: &dyn TextStage, cases: &[(&str, &str)]) {
+        for (input, expected) in cases {
+            assert_eq!(stage.apply(input), *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn whitespace() {
+        check(
+            &Whitespace,
+            &[
+                ("  Hello   world \t", "Hello world"),
+                ("one\n\n  two  \n", "one\ntwo"),
+                ("   ", ""),
+            ],
+        );
+    }
+
+    #[test]
+    fn join_segments() {
+        let space = JoinSegments {
+            separator: " ".to_string(),
+        };
+        check(
+            &space,
+            &[("First.\nSecond.", "First. Second."), ("One", "One")],
+        );
+        let newline = JoinSegments {
+            separator: "\n".to_string(),
+        };
+        check(&newline, &[("First.\nSecond.", "First.\nSecond.")]);
+    }
+
+    #[test]
+    fn trailing_punctuation() {
+        let policy = |policy: &str| TrailingPunctuation {
+            policy: policy.to_string(),
+        };
+        check(
+            &policy("strip"),
+            &[("Done.", "Done"), ("Wait…", "Wait"), ("Really?", "Really?")],
+        );
+        check(
+            &policy("ensure"),
+            &[
+                ("Done", "Done."),
+                ("Done.", "Done."),
+                ("Really?", "Really?"),
+                ("", ""),
+            ],
+        );
+        check(&policy("keep"), &[("Done", "Done"), ("Done.", "Done.")]);
+    }
+
+    #[test]
+    fn capitalization() {
+        let policy = |policy: &str| Capitalization {
+            policy: policy.to_string(),
+        };
+        check(
+            &policy("upper"),
+            &[("hello", "Hello"), ("«quote»", "«Quote»"), ("42", "42")],
+        );
+        check(
+            &policy("lower"),
+            &[
+                ("Hello", "hello"),
+                ("I think so", "I think so"),
+                ("NASA launched", "NASA launched"),
+            ],
+        );
+        check(&policy("keep"), &[("hello", "hello"), ("Hello", "Hello")]);
+    }
+
+    #[test]
+    fn trailing_space() {
+        check(
+            &TrailingSpace { enabled: true },
+            &[("Done.", "Done. "), ("Done. ", "Done. "), ("", "")],
+        );
+        check(&TrailingSpace { enabled: false }, &[("Done.", "Done.")]);
+    }
+
+    #[test]
+    fn profile_selects_stages() {
+        let settings = PostProcessing {
+            profiles: HashMap::from([(
+                "plain".to_string(),
+                vec!["whitespace".to_string(), "no_such_stage".to_string()],
+            )]),
+            ..Default::default()
+        };
+        let pipeline = Pipeline::new(&settings, "en", Some("plain"));
+        assert_eq!(pipeline.apply("  um   hello \n world "), "um hello\nworld");
+    }
+}
@@ -0,0 +1,88 @@
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::accessibility;
+use crate::config;
+use crate::focused_window;
+
+lazy_static! {
+    // Recent dictations per X11 window, for when the text before the caret can't be read
+    static ref RECENT: Mutex<HashMap<u32, String>> = Mutex::new(HashMap::new());
+}
+
+/// The window a dictation goes to and the text it continues
+pub struct DictationContext {
+    window: Option<u32>,
+    max_chars: usize,
+    /// Initial prompt for whisper; None when disabled or nothing is known
+    pub prompt: Option<String>,
+}
+
+impl DictationContext {
+    /// Look at the focused window: the text before the caret (AT-SPI),
+    /// or else what was last dictated into the same window
+    pub fn capture() -> Self {
+        let settings = config::get_prompt_context();
+        if !settings.enabled || settings.max_chars == 0 {
+            return Self {
+                window: None,
+                max_chars: 0,
+                prompt: None,
+            };
+        }
+        let window = focused_window::active_window_id();
+        let before = accessibility::text_before_caret(settings.max_chars).or_else(|| {
+            let recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+            window.and_then(|id| recent.get(&id).cloned())
+        });
+        let prompt = before
+            .map(|text| tail_words(&text, settings.max_chars))
+            .filter(|text| !text.is_empty());
+        if let Some(prompt) = &prompt {
+            println!("Prompting with {} characters of context", prompt.chars().count());
+        }
+        Self {
+            window,
+            max_chars: settings.max_chars,
+            prompt,
+        }
+    }
+
+    /// Remember `transcript` as context for the next dictation into the same window
+    pub fn remember(&self, transcript: &str) {
+        let Some(window) = self.window else {
+            return;
+        };
+        let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+        let text = recent.entry(window).or_default();
+        if !text.is_empty() && !text.ends_with(char::is_whitespace) {
+            text.push(' ');
+        }
+        text.push_str(transcript.trim());
+        *text = tail_words(text, self.max_chars);
+    }
+}
+
+// The last `max_chars` characters of `text`, starting at a word boundary
+fn tail_words(text: &str, max_chars: usize) -> String {
+    // whisper.cpp takes the prompt as a C string
+    let text = text.replace('\0', "");
+    let text = text.trim();
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text.to_string();
+    }
+    let start = text
+        .char_indices()
+        .nth(count - max_chars)
+        .map_or(0, |(i, _)| i);
+    let tail = &text[start..];
+    // Drop the partial word at the cut
+    match tail.find(char::is_whitespace) {
+        Some(space) if !text[..start].ends_with(char::is_whitespace) => {
+            tail[space..].trim_start().to_string()
+        }
+        _ => tail.to_string(),
+    }
+}
@@ -0,0 +1,306 @@
+use reqwest::blocking::multipart::{Form, Part};
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+use crate::audio_utils;
+use crate::config::RemoteEngine;
+use crate::http_client;
+use crate::transcriber::Transcriber;
+
+/// Why a request to the remote engine failed
+#[derive(Debug)]
+pub enum RemoteError {
+    /// The server could not be reached (connection refused, timeout, gateway down, offline mode)
+    Unreachable(String),
+    /// The server answered, but not with a transcript
+    Failed(String),
+}
+
+impl std::fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoteError::Unreachable(e) => write!(f, "Remote engine unreachable: {}", e),
+            RemoteError::Failed(e) => write!(f, "Remote engine failed: {}", e),
+        }
+    }
+}
+
+/// The remote transcript, or the one from `local` when the server couldn't be reached
+/// and a local fallback is enabled. Errors from a server that answered are returned as they are.
+pub fn or_local_fallback(
+    remote: Result<String, RemoteError>,
+    local: Option<impl FnOnce() -> Result<String, String>>,
+) -> Result<String, String> {
+    match (remote, local) {
+        (Ok(text), _) => Ok(text),
+        (Err(RemoteError::Unreachable(e)), Some(local)) => {
+            eprintln!("Remote engine unreachable ({}); transcribing locally", e);
+            local()
+        }
+        (Err(e), _) => Err(e.to_string()),
+    }
+}
+
+/// Client for an OpenAI-compatible speech-to-text server
+pub struct RemoteTranscriber {
+    settings: RemoteEngine,
+}
+
+impl RemoteTranscriber {
+    pub fn new(settings: RemoteEngine) -> Self {
+        Self { settings }
+    }
+
+    fn client(&self) -> Result<Client, RemoteError> {
+        http_client::blocking_client(Duration::from_secs(self.settings.timeout_secs.max(1)))
+            .map_err(RemoteError::Unreachable)
+    }
+
+    fn endpoint(&self, kind: &str) -> String {
+        format!("{}/audio/{}", self.settings.url.trim_end_matches('/'), kind)
+    }
+
+    /// POST the audio as a WAV file to `/audio/transcriptions` or `/audio/translations`
+    pub fn request(
+        &self,
+        kind: &str,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        language: Option<&str>,
+    ) -> Result<String, RemoteError> {
+        let audio = audio_utils::to_whisper_input(samples, sample_rate, channels);
+        let wav = audio_utils::encode_wav(&audio, audio_utils::WHISPER_SAMPLE_RATE)
+            .map_err(RemoteError::Failed)?;
+
+        let file = Part::bytes(wav)
+            .file_name("audio.wav")
+            .mime_str("audio/wav")
+            .map_err(|e| RemoteError::Failed(e.to_string()))?;
+        let mut form = Form::new()
+            .part("file", file)
+            .text("model", self.settings.model.clone())
+            .text("response_format", "json");
+        if let Some(lang) = language.filter(|_| kind == "transcriptions") {
+            let lang_code = if lang.len() >= 2 { &lang[0..2] } else { lang };
+            form = form.text("language", lang_code.to_string());
+        }
+
+        let url = self.endpoint(kind);
+        println!("Sending {} samples to {}", audio.len(), url);
+        let mut request = self.client()?.post(&url).multipart(form);
+        if let Some(key) = self.settings.api_key.as_deref().filter(|k| !k.is_empty()) {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request.send().map_err(|e| {
+            if e.is_connect() || e.is_timeout() {
+                RemoteError::Unreachable(e.to_string())
+            } else {
+                RemoteError::Failed(e.to_string())
+            }
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            let message = format!("HTTP {} from {}: {}", status, url, body.trim());
+            return Err(match status {
+                StatusCode::BAD_GATEWAY
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT => RemoteError::Unreachable(message),
+                _ => RemoteError::Failed(message),
+            });
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .map_err(|e| RemoteError::Failed(format!("Invalid response from {}: {}", url, e)))?;
+        body.get("text")
+            .and_then(|t| t.as_str())
+            .map(|t| t.trim().to_string())
+            .ok_or_else(|| RemoteError::Failed(format!("Response from {} has no \"text\"", url)))
+    }
+}
+
+impl Transcriber for RemoteTranscriber {
+    fn transcribe_samples(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        language: Option<&str>,
+    ) -> Result<String, String> {
+        self.request("transcriptions", samples, sample_rate, channels, language)
+            .map_err(|e| e.to_string())
+    }
+
+    fn translate_samples(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        language: Option<&str>,
+    ) -> Result<String, String> {
+        self.request("translations", samples, sample_rate, channels, language)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use tiny_http::{Response, Server};
+
+    /// A request seen by the stand-in server: URL, Authorization, Content-Type and body
+    struct Seen {
+        url: String,
+        authorization: Option<String>,
+        content_type: String,
+        body: String,
+    }
+
+    /// Local stand-in for a transcription server that answers every request with `status` and `body`
+    fn serve(status: u16, body: &'static str) -> (String, Arc<Mutex<Vec<Seen>>>) {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let base = format!("http://{}/v1", server.server_addr().to_ip().unwrap());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        std::thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let header = |name: &'static str| {
+                    request
+                        .headers()
+                        .iter()
+                        .find(|h| h.field.equiv(name))
+                        .map(|h| h.value.to_string())
+                };
+                let authorization = header("Authorization");
+                let content_type = header("Content-Type").unwrap_or_default();
+                let mut bytes = Vec::new();
+                let _ = request.as_reader().read_to_end(&mut bytes);
+                log.lock().unwrap().push(Seen {
+                    url: request.url().to_string(),
+                    authorization,
+                    content_type,
+                    body: String::from_utf8_lossy(&bytes).to_string(),
+                });
+                let _ = request.respond(Response::from_string(body).with_status_code(status));
+            }
+        });
+        (base, seen)
+    }
+
+    fn engine(url: &str, api_key: Option<&str>) -> RemoteTranscriber {
+        RemoteTranscriber::new(RemoteEngine {
+            url: url.to_string(),
+            api_key: api_key.map(str::to_string),
+            model: "whisper-1".to_string(),
+            timeout_secs: 10,
+            fallback_to_local: true,
+        })
+    }
+
+    // Default settings, so a proxy or offline mode in the user's config doesn't interfere
+    fn default_config() -> (std::sync::MutexGuard<'static, ()>, config::ConfigFileGuard) {
+        let lock = config::TEST_CONFIG_LOCK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let path =
+            std::env::temp_dir().join(format!("voice_input_remote_{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"selected_model": "small"}"#).unwrap();
+        (lock, config::use_config_file(Some(path)))
+    }
+
+    #[test]
+    fn request_shape() {
+        let _config = default_config();
+        let (base, seen) = serve(200, r#"{"text": " hello world "}"#);
+
+        let remote = engine(&format!("{}/", base), Some("secret"));
+        let silence = vec![0.0f32; 16000];
+        let text = remote
+            .request("transcriptions", &silence, 16000, 1, Some("en-US"))
+            .unwrap();
+        assert_eq!(text, "hello world");
+        remote
+            .request("translations", &silence, 16000, 1, Some("de"))
+            .unwrap();
+        engine(&base, None)
+            .request("transcriptions", &silence, 16000, 1, None)
+            .unwrap();
+
+        let seen = seen.lock().unwrap();
+        let request = &seen[0];
+        assert_eq!(request.url, "/v1/audio/transcriptions");
+        assert_eq!(request.authorization.as_deref(), Some("Bearer secret"));
+        assert!(
+            request
+                .content_type
+                .starts_with("multipart/form-data; boundary="),
+            "{}",
+            request.content_type
+        );
+        for part in [
+            "name=\"file\"; filename=\"audio.wav\"\r\nContent-Type: audio/wav\r\n\r\nRIFF",
+            "name=\"model\"\r\n\r\nwhisper-1\r\n",
+            "name=\"response_format\"\r\n\r\njson\r\n",
+            "name=\"language\"\r\n\r\nen\r\n",
+        ] {
+            assert!(request.body.contains(part), "missing {:?}", part);
+        }
+
+        // Translations don't take a language; no key means no Authorization header
+        assert_eq!(seen[1].url, "/v1/audio/translations");
+        assert!(!seen[1].body.contains("name=\"language\""));
+        assert_eq!(seen[2].authorization, None);
+    }
+
+    #[test]
+    fn unreachable_servers_fall_back_to_local() {
+        let _config = default_config();
+        let (unavailable, _) = serve(503, "model loading");
+        let refused = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}/v1", listener.local_addr().unwrap())
+        };
+
+        for url in [unavailable, refused] {
+            let silence = vec![0.0f32; 16000];
+            let remote = engine(&url, None).request("transcriptions", &silence, 16000, 1, None);
+            assert!(
+                matches!(remote, Err(RemoteError::Unreachable(_))),
+                "{}: {:?}",
+                url,
+                remote
+            );
+            let local = Some(|| Ok("local".to_string()));
+            assert_eq!(or_local_fallback(remote, local), Ok("local".to_string()));
+        }
+    }
+
+    #[test]
+    fn failed_requests_do_not_fall_back() {
+        let _config = default_config();
+        let (base, _) = serve(400, r#"{"error": "unsupported audio"}"#);
+
+        let silence = vec![0.0f32; 16000];
+        let remote = engine(&base, None).request("transcriptions", &silence, 16000, 1, None);
+        assert!(
+            matches!(remote, Err(RemoteError::Failed(_))),
+            "{:?}",
+            remote
+        );
+        let local = Some(|| -> Result<String, String> { panic!("fell back to local") });
+        let error = or_local_fallback(remote, local).unwrap_err();
+        assert!(
+            error.contains("HTTP 400") && error.contains("unsupported audio"),
+            "{}",
+            error
+        );
+    }
+}
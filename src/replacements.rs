@@ -0,0 +1,379 @@
+use lazy_static::lazy_static;
+use regex::{Captures, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::config;
+use crate::post_processing::TextStage;
+
+/// One entry of the replacement dictionary
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Rule {
+    /// Text to find: a phrase matched on word boundaries, or a regex when `regex` is set
+    pub from: String,
+    /// Replacement; regex rules may refer to groups as `${1}` or `${name}`
+    pub to: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub regex: bool,
+    /// Only apply to this language (2-letter code); all languages when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Match case exactly instead of case-insensitively
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub case_sensitive: bool,
+}
+
+/// The replacement dictionary file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Dictionary {
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+struct CompiledRule {
+    pattern: Regex,
+    rule: Rule,
+}
+
+// Rules of the dictionary file as of its last modification time
+struct Loaded {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    rules: Arc<Vec<CompiledRule>>,
+}
+
+lazy_static! {
+    static ref LOADED: Mutex<Option<Loaded>> = Mutex::new(None);
+}
+
+fn compile(rule: &Rule) -> Result<Regex, String> {
+    let source = if rule.regex {
+        rule.from.clone()
+    } else {
+        // Anchor phrases on word boundaries where they start/end with a word character
+        let phrase = rule.from.trim();
+        let escaped: Vec<String> = phrase.split_whitespace().map(regex::escape).collect();
+        let starts_word = phrase.starts_with(|c: char| c.is_alphanumeric());
+        let ends_word = phrase.ends_with(|c: char| c.is_alphanumeric());
+        format!(
+            "{}{}{}",
+            if starts_word { r"\b" } else { "" },
+            escaped.join(r"\s+"),
+            if ends_word { r"\b" } else { "" }
+        )
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(!rule.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid replacement rule '{}': {}", rule.from, e))
+}
+
+/// Read and validate a dictionary file
+pub fn read_dictionary(path: &Path) -> Result<Dictionary, String> {
+    let data = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let dictionary: Dictionary = serde_json::from_str(&data)
+        .map_err(|e| format!("Invalid replacement dictionary {}: {}", path.display(), e))?;
+    for rule in &dictionary.rules {
+        compile(rule)?;
+    }
+    Ok(dictionary)
+}
+
+fn write_dictionary(path: &Path, dictionary: &Dictionary) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(dictionary).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn dictionary_path() -> Result<PathBuf, String> {
+    config::get_replacements_file()
+        .ok_or_else(|| "Could not determine the replacement dictionary path".to_string())
+}
+
+// Compiled rules, re-read whenever the file changes on disk
+fn current_rules() -> Arc<Vec<CompiledRule>> {
+    let Ok(path) = dictionary_path() else {
+        return Arc::new(Vec::new());
+    };
+    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+
+    let mut loaded = LOADED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(current) = loaded.as_ref() {
+        if current.path == path && current.modified == modified {
+            return current.rules.clone();
+        }
+    }
+
+    let rules = if modified.is_some() {
+        load_rules(&path)
+    } else {
+        Vec::new()
+    };
+    let rules = Arc::new(rules);
+    *loaded = Some(Loaded {
+        path,
+        modified,
+        rules: rules.clone(),
+    });
+    rules
+}
+
+// Invalid rules are skipped so one typo doesn't disable the whole dictionary
+fn load_rules(path: &Path) -> Vec<CompiledRule> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+    let dictionary: Dictionary = match serde_json::from_str(&data) {
+        Ok(d) => d,
+        Err(e) => {
+            eprintln!("Invalid replacement dictionary {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+    println!(
+        "Loaded {} replacement rule(s) from {}",
+        dictionary.rules.len(),
+        path.display()
+    );
+    dictionary
+        .rules
+        .into_iter()
+        .filter_map(|rule| match compile(&rule) {
+            Ok(pattern) => Some(CompiledRule { pattern, rule }),
+            Err(e) => {
+                eprintln!("{}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+// Adapt a literal replacement to the case the phrase was spoken/written in
+fn match_case(matched: &str, replacement: &str) -> String {
+    let letters: Vec<char> = matched.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        return replacement.to_uppercase();
+    }
+    if letters.first().is_some_and(|c| c.is_uppercase()) {
+        let mut chars = replacement.chars();
+        if let Some(first) = chars.next() {
+            return first.to_uppercase().chain(chars).collect();
+        }
+    }
+    replacement.to_string()
+}
+
+/// Apply the replacement dictionary rules for one language
+pub struct Replacements {
+    rules: Arc<Vec<CompiledRule>>,
+    language: String,
+}
+
+impl Replacements {
+    /// The rules of the dictionary file (reloaded if it changed) for `language`
+    pub fn for_language(language: &str) -> Self {
+        Self {
+            rules: current_rules(),
+            language: language.get(..2).unwrap_or(language).to_lowercase(),
+        }
+    }
+}
+
+impl TextStage for Replacements {
+    fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        let applicable = self.rules.iter().filter(|r| {
+            r.rule
+                .language
+                .as_deref()
+                .is_none_or(|l| l.eq_ignore_ascii_case(&self.language))
+        });
+        for compiled in applicable {
+            let rule = &compiled.rule;
+            text = if rule.regex {
+                compiled
+                    .pattern
+                    .replace_all(&text, rule.to.as_str())
+                    .into_owned()
+            } else if rule.case_sensitive {
+                compiled
+                    .pattern
+                    .replace_all(&text, regex::NoExpand(&rule.to))
+                    .into_owned()
+            } else {
+                compiled
+                    .pattern
+                    .replace_all(&text, |caps: &Captures| match_case(&caps[0], &rule.to))
+                    .into_owned()
+            };
+        }
+        text
+    }
+}
+
+/// `voice_input replacements list`
+pub fn list() -> Result<(), String> {
+    let path = dictionary_path()?;
+    if !path.exists() {
+        println!("No replacement dictionary at {}", path.display());
+        return Ok(());
+    }
+    let dictionary = read_dictionary(&path)?;
+    println!("{} rule(s) in {}", dictionary.rules.len(), path.display());
+    for rule in &dictionary.rules {
+        let mut flags = Vec::new();
+        if rule.regex {
+            flags.push("regex".to_string());
+        }
+        if rule.case_sensitive {
+            flags.push("case-sensitive".to_string());
+        }
+        if let Some(language) = &rule.language {
+            flags.push(language.clone());
+        }
+        let flags = if flags.is_empty() {
+            String::new()
+        } else {
+            format!("  [{}]", flags.join(", "))
+        };
+        println!("  {} -> {}{}", rule.from, rule.to, flags);
+    }
+    Ok(())
+}
+
+/// `voice_input replacements import <file>`: merge rules into the dictionary.
+/// Imported rules replace existing ones with the same pattern and language.
+pub fn import(file: &Path) -> Result<(), String> {
+    let imported = read_dictionary(file)?;
+    let path = dictionary_path()?;
+    let mut dictionary = if path.exists() {
+        read_dictionary(&path)?
+    } else {
+        Dictionary::default()
+    };
+
+    let (mut added, mut updated) = (0, 0);
+    for rule in imported.rules {
+        let same = dictionary.rules.iter_mut().find(|r| {
+            r.from == rule.from && r.regex == rule.regex && r.language == rule.language
+        });
+        match same {
+            Some(existing) if *existing == rule => {}
+            Some(existing) => {
+                *existing = rule;
+                updated += 1;
+            }
+            None => {
+                dictionary.rules.push(rule);
+                added += 1;
+            }
+        }
+    }
+    write_dictionary(&path, &dictionary)?;
+    println!(
+        "Imported {} new and {} updated rule(s) into {}",
+        added,
+        updated,
+        path.display()
+    );
+    Ok(())
+}
+
+/// `voice_input replacements export <file>`
+pub fn export(file: &Path) -> Result<(), String> {
+    let path = dictionary_path()?;
+    let dictionary = if path.exists() {
+        read_dictionary(&path)?
+    } else {
+        Dictionary::default()
+    };
+    write_dictionary(file, &dictionary)?;
+    println!(
+        "Exported {} rule(s) to {}",
+        dictionary.rules.len(),
+        file.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(from: &str, to: &str) -> Rule {
+        Rule {
+            from: from.to_string(),
+            to: to.to_string(),
+            regex: false,
+            language: None,
+            case_sensitive: false,
+        }
+    }
+
+    fn check(rules: Vec<Rule>, language: &str, cases: &[(&str, &str)]) {
+        let compiled = rules
+            .into_iter()
+            .map(|rule| CompiledRule {
+                pattern: compile(&rule).unwrap(),
+                rule,
+            })
+            .collect();
+        let stage = Replacements {
+            rules: Arc::new(compiled),
+            language: language.to_string(),
+        };
+        for (input, expected) in cases {
+            assert_eq!(stage.apply(input), *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn literal_phrases() {
+        check(
+            vec![rule("git hub", "GitHub"), rule("kubernetes", "Kubernetes")],
+            "en",
+            &[
+                ("I use git hub daily", "I use GitHub daily"),
+                ("I use git  hub daily", "I use GitHub daily"),
+                ("GIT HUB", "GITHUB"),
+                ("deploy to kubernetes.", "deploy to Kubernetes."),
+                ("gitHubber", "gitHubber"),
+            ],
+        );
+    }
+
+    #[test]
+    fn regex_and_case() {
+        let mut size = rule(r"(\d+) by (\d+)", "${1}×${2}");
+        size.regex = true;
+        let mut exact = rule("Go", "Golang");
+        exact.case_sensitive = true;
+        check(
+            vec![size, exact],
+            "en",
+            &[
+                ("a 1920 by 1080 screen", "a 1920×1080 screen"),
+                ("Go is fun, go on", "Golang is fun, go on"),
+            ],
+        );
+    }
+
+    #[test]
+    fn language_filter() {
+        let mut russian = rule("питон", "Python");
+        russian.language = Some("ru".to_string());
+        check(vec![russian.clone()], "en", &[("питон", "питон")]);
+        check(vec![russian], "ru", &[("пишу на питон", "пишу на Python")]);
+    }
+}
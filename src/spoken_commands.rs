@@ -0,0 +1,329 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::post_processing::TextStage;
+
+// Built-in commands per language: spoken phrase -> inserted text
+const ENGLISH: &[(&str, &str)] = &[
+    ("comma", ","),
+    ("period", "."),
+    ("full stop", "."),
+    ("question mark", "?"),
+    ("exclamation mark", "!"),
+    ("exclamation point", "!"),
+    ("colon", ":"),
+    ("semicolon", ";"),
+    ("ellipsis", "…"),
+    ("dash", "—"),
+    ("open paren", "("),
+    ("open parenthesis", "("),
+    ("close paren", ")"),
+    ("close parenthesis", ")"),
+    ("open quote", "“"),
+    ("close quote", "”"),
+    ("new line", "\n"),
+    ("newline", "\n"),
+    ("new paragraph", "\n\n"),
+];
+
+const RUSSIAN: &[(&str, &str)] = &[
+    ("запятая", ","),
+    ("точка", "."),
+    ("точка с запятой", ";"),
+    ("вопросительный знак", "?"),
+    ("восклицательный знак", "!"),
+    ("двоеточие", ":"),
+    ("многоточие", "…"),
+    ("тире", "—"),
+    ("открыть скобку", "("),
+    ("закрыть скобку", ")"),
+    ("открыть кавычки", "«"),
+    ("закрыть кавычки", "»"),
+    ("новая строка", "\n"),
+    ("с новой строки", "\n"),
+    ("новый абзац", "\n\n"),
+];
+
+// Command words that are also common nouns ("the trial period", "точка зрения"):
+// taken as words after a determiner or when the sentence goes on right after them
+const NOUN_COMMANDS: &[&str] = &["period", "colon", "dash", "точка", "тире"];
+const DETERMINERS: &[&str] = &[
+    "a",
+    "an",
+    "the",
+    "this",
+    "that",
+    "each",
+    "every",
+    "any",
+    "no",
+    "my",
+    "your",
+    "his",
+    "her",
+    "its",
+    "our",
+    "their",
+    "эта",
+    "та",
+    "одна",
+    "каждая",
+];
+
+/// Commands for `language`: the built-in table extended (or overridden) by `extra`.
+/// An empty replacement in `extra` disables a built-in command.
+pub fn commands_for(language: &str, extra: &HashMap<String, String>) -> Vec<(String, String)> {
+    let builtin = match language.get(..2).unwrap_or(language) {
+        "en" => ENGLISH,
+        "ru" => RUSSIAN,
+        _ => &[],
+    };
+    let mut commands: HashMap<String, String> = builtin
+        .iter()
+        .map(|(phrase, text)| (phrase.to_string(), text.to_string()))
+        .collect();
+    for (phrase, text) in extra {
+        let phrase = phrase.trim().to_lowercase();
+        if text.is_empty() {
+            commands.remove(&phrase);
+        } else {
+            commands.insert(phrase, text.clone());
+        }
+    }
+    commands.into_iter().collect()
+}
+
+/// Replace spoken punctuation and formatting commands ("comma", "new line") with what they stand for
+pub struct SpokenCommands {
+    // Phrase words (lowercase) and replacement, longest phrases first
+    commands: Vec<(Vec<String>, String)>,
+}
+
+impl SpokenCommands {
+    pub fn new(commands: Vec<(String, String)>) -> Self {
+        let mut commands: Vec<(Vec<String>, String)> = commands
+            .into_iter()
+            .map(|(phrase, text)| {
+                let words: Vec<String> = phrase
+                    .split_whitespace()
+                    .map(|w| w.to_lowercase())
+                    .collect();
+                (words, text)
+            })
+            .filter(|(words, _)| !words.is_empty())
+            .collect();
+        commands.sort_by_key(|(words, _)| Reverse(words.len()));
+        Self { commands }
+    }
+
+    // Command starting at `tokens[i]`: (number of tokens it spans, replacement)
+    fn match_at(&self, tokens: &[&str], i: usize) -> Option<(usize, &str)> {
+        self.commands.iter().find_map(|(words, text)| {
+            let candidate = tokens.get(i..i + words.len())?;
+            candidate
+                .iter()
+                .zip(words)
+                .all(|(token, word)| word_core(token).to_lowercase() == *word)
+                .then_some((words.len(), text.as_str()))
+        })
+    }
+
+    fn apply_line(&self, line: &str) -> String {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let mut pieces: Vec<Piece> = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let command = self
+                .match_at(&tokens, i)
+                .filter(|(len, _)| !(*len == 1 && is_noun_use(&tokens, i)));
+            match command {
+                Some((len, text)) => {
+                    let piece = Piece::command(text);
+                    // Whisper often punctuates around the command word itself ("Hello, comma, world")
+                    if piece.glue_left && !text.contains('\n') {
+                        if let Some(prev) = pieces.last_mut().filter(|p| !p.glue_right) {
+                            prev.text = prev
+                                .text
+                                .trim_end_matches(is_clause_punctuation)
+                                .to_string();
+                        }
+                    }
+                    pieces.push(piece);
+                    i += len;
+                }
+                None => {
+                    let capitalize = pieces.last().is_some_and(|p| p.ends_sentence);
+                    pieces.push(Piece::word(tokens[i], capitalize));
+                    i += 1;
+                }
+            }
+        }
+
+        let mut out = String::new();
+        for (n, piece) in pieces.iter().enumerate() {
+            if n > 0 && !piece.glue_left && !pieces[n - 1].glue_right {
+                out.push(' ');
+            }
+            out.push_str(&piece.text);
+        }
+        out
+    }
+}
+
+impl TextStage for SpokenCommands {
+    fn apply(&self, text: &str) -> String {
+        if self.commands.is_empty() {
+            return text.to_string();
+        }
+        text.lines()
+            .map(|line| self.apply_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// A word or command output with how it attaches to its neighbours
+struct Piece {
+    text: String,
+    // No space before (closing punctuation, newlines)
+    glue_left: bool,
+    // No space after (opening brackets and quotes, newlines)
+    glue_right: bool,
+    // The next word starts a sentence
+    ends_sentence: bool,
+}
+
+impl Piece {
+    fn word(token: &str, capitalize: bool) -> Self {
+        let text = if capitalize {
+            let mut chars = token.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        } else {
+            token.to_string()
+        };
+        Self {
+            text,
+            glue_left: false,
+            glue_right: false,
+            ends_sentence: false,
+        }
+    }
+
+    fn command(text: &str) -> Self {
+        let newline = text.contains('\n');
+        Self {
+            text: text.to_string(),
+            glue_left: newline
+                || text.starts_with([',', '.', '?', '!', ':', ';', '…', ')', '»', '”']),
+            glue_right: newline || text.ends_with(['(', '«', '“']),
+            ends_sentence: newline || text.ends_with(['.', '?', '!']),
+        }
+    }
+}
+
+// Whether the one-word command at `tokens[i]` is used as a noun instead
+fn is_noun_use(tokens: &[&str], i: usize) -> bool {
+    let token = tokens[i];
+    if !NOUN_COMMANDS.contains(&word_core(token).to_lowercase().as_str()) {
+        return false;
+    }
+    let after_determiner = i > 0
+        && word_core(tokens[i - 1]) == tokens[i - 1]
+        && DETERMINERS.contains(&tokens[i - 1].to_lowercase().as_str());
+    let sentence_goes_on = word_core(token).len() == token.len()
+        && tokens
+            .get(i + 1)
+            .and_then(|next| next.chars().next())
+            .is_some_and(char::is_lowercase);
+    after_determiner || sentence_goes_on
+}
+
+fn is_clause_punctuation(c: char) -> bool {
+    matches!(c, ',' | '.' | ';' | ':')
+}
+
+// The word inside surrounding punctuation: "comma," -> "comma"
+fn word_core(token: &str) -> &str {
+    token.trim_matches(|c: char| !c.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(language: &str, extra: &[(&str, &str)], cases: &[(&str, &str)]) {
+        let extra = extra
+            .iter()
+            .map(|(phrase, text)| (phrase.to_string(), text.to_string()))
+            .collect();
+        let stage = SpokenCommands::new(commands_for(language, &extra));
+        for (input, expected) in cases {
+            assert_eq!(stage.apply(input), *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn english_commands() {
+        check(
+            "en",
+            &[],
+            &[
+                ("Hello comma world period", "Hello, world."),
+                ("Hello, comma, world.", "Hello, world."),
+                ("Is it question mark yes", "Is it? Yes"),
+                ("first new line second", "first\nSecond"),
+                ("see open paren below close paren", "see (below)"),
+                ("wait full stop", "wait."),
+            ],
+        );
+    }
+
+    #[test]
+    fn russian_commands() {
+        check(
+            "ru",
+            &[],
+            &[
+                ("Привет запятая мир точка", "Привет, мир."),
+                ("точка с запятой", ";"),
+                ("с точки зрения", "с точки зрения"),
+            ],
+        );
+    }
+
+    #[test]
+    fn nouns_are_not_commands() {
+        check(
+            "en",
+            &[],
+            &[
+                (
+                    "The trial period ends in May.",
+                    "The trial period ends in May.",
+                ),
+                ("for a period of time", "for a period of time"),
+                ("Send it period", "Send it."),
+            ],
+        );
+        check(
+            "ru",
+            &[],
+            &[("Это точка зрения автора", "Это точка зрения автора")],
+        );
+    }
+
+    #[test]
+    fn extra_commands() {
+        check(
+            "en",
+            &[("smiley", ":)"), ("period", "")],
+            &[
+                ("nice smiley", "nice:)"),
+                ("Send it period", "Send it period"),
+            ],
+        );
+    }
+}
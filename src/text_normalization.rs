@@ -0,0 +1,1151 @@
+use crate::post_processing::TextStage;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Lang {
+    English,
+    Russian,
+}
+
+// What a number word contributes
+#[derive(Clone, Copy, PartialEq)]
+enum NumWord {
+    // 0-9
+    Unit(u64),
+    // 10-19
+    Teen(u64),
+    // 20, 30, ... 90
+    Tens(u64),
+    // Russian 100, 200, ... 900
+    Hundreds(u64),
+    // English "hundred" multiplies what precedes it
+    Hundred,
+    // thousand, million, billion
+    Scale(u64),
+    // Ends the number: first, twenty-fifth, третьего
+    Ordinal(u64),
+}
+
+const EN_UNITS: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const EN_TEENS: &[&str] = &[
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+const EN_TENS: &[&str] = &[
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+const EN_ORDINALS: &[(&str, u64)] = &[
+    ("first", 1),
+    ("second", 2),
+    ("third", 3),
+    ("fourth", 4),
+    ("fifth", 5),
+    ("sixth", 6),
+    ("seventh", 7),
+    ("eighth", 8),
+    ("ninth", 9),
+    ("tenth", 10),
+    ("eleventh", 11),
+    ("twelfth", 12),
+    ("thirteenth", 13),
+    ("fourteenth", 14),
+    ("fifteenth", 15),
+    ("sixteenth", 16),
+    ("seventeenth", 17),
+    ("eighteenth", 18),
+    ("nineteenth", 19),
+    ("twentieth", 20),
+    ("thirtieth", 30),
+    ("fortieth", 40),
+    ("fiftieth", 50),
+    ("sixtieth", 60),
+    ("seventieth", 70),
+    ("eightieth", 80),
+    ("ninetieth", 90),
+    ("hundredth", 100),
+    ("thousandth", 1000),
+];
+const EN_MONTHS: &[&str] = &[
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+// Russian number words with their case forms
+const RU_NUMBERS: &[(&[&str], u64)] = &[
+    (&["ноль", "нуля", "нулю", "нулём", "нулем"], 0),
+    (
+        &[
+            "один",
+            "одна",
+            "одно",
+            "одного",
+            "одной",
+            "одному",
+            "одним",
+            "одну",
+            "одном",
+        ],
+        1,
+    ),
+    (&["два", "две", "двух", "двум", "двумя"], 2),
+    (&["три", "трёх", "трех", "трём", "трем", "тремя"], 3),
+    (
+        &[
+            "четыре",
+            "четырёх",
+            "четырех",
+            "четырём",
+            "четырем",
+            "четырьмя",
+        ],
+        4,
+    ),
+    (&["пять", "пяти", "пятью"], 5),
+    (&["шесть", "шести", "шестью"], 6),
+    (&["семь", "семи", "семью"], 7),
+    (&["восемь", "восьми", "восемью", "восьмью"], 8),
+    (&["девять", "девяти", "девятью"], 9),
+    (&["десять", "десяти", "десятью"], 10),
+    (&["одиннадцать", "одиннадцати"], 11),
+    (&["двенадцать", "двенадцати"], 12),
+    (&["тринадцать", "тринадцати"], 13),
+    (&["четырнадцать", "четырнадцати"], 14),
+    (&["пятнадцать", "пятнадцати"], 15),
+    (&["шестнадцать", "шестнадцати"], 16),
+    (&["семнадцать", "семнадцати"], 17),
+    (&["восемнадцать", "восемнадцати"], 18),
+    (&["девятнадцать", "девятнадцати"], 19),
+    (&["двадцать", "двадцати", "двадцатью"], 20),
+    (&["тридцать", "тридцати", "тридцатью"], 30),
+    (&["сорок", "сорока"], 40),
+    (&["пятьдесят", "пятидесяти"], 50),
+    (&["шестьдесят", "шестидесяти"], 60),
+    (&["семьдесят", "семидесяти"], 70),
+    (&["восемьдесят", "восьмидесяти"], 80),
+    (&["девяносто", "девяноста"], 90),
+    (&["сто", "ста"], 100),
+    (&["двести", "двухсот", "двумстам"], 200),
+    (&["триста", "трёхсот", "трехсот"], 300),
+    (&["четыреста", "четырёхсот", "четырехсот"], 400),
+    (&["пятьсот", "пятисот"], 500),
+    (&["шестьсот", "шестисот"], 600),
+    (&["семьсот", "семисот"], 700),
+    (&["восемьсот", "восьмисот"], 800),
+    (&["девятьсот", "девятисот"], 900),
+];
+// Forms that are also common other words: "семью" (family), "сорока" (magpie).
+// Alone they are numbers only before a unit, currency or percent.
+const RU_AMBIGUOUS: &[&str] = &["семью", "сорока"];
+const RU_SCALES: &[(&str, u64)] = &[
+    ("тысяч", 1_000),
+    ("миллион", 1_000_000),
+    ("миллиард", 1_000_000_000),
+];
+// Ordinal stems; any adjective ending follows (первый, первого, первая, ...)
+const RU_ORDINAL_STEMS: &[(&str, u64)] = &[
+    ("перв", 1),
+    ("втор", 2),
+    ("четвёрт", 4),
+    ("четверт", 4),
+    ("пят", 5),
+    ("шест", 6),
+    ("седьм", 7),
+    ("восьм", 8),
+    ("девят", 9),
+    ("десят", 10),
+    ("одиннадцат", 11),
+    ("двенадцат", 12),
+    ("тринадцат", 13),
+    ("четырнадцат", 14),
+    ("пятнадцат", 15),
+    ("шестнадцат", 16),
+    ("семнадцат", 17),
+    ("восемнадцат", 18),
+    ("девятнадцат", 19),
+    ("двадцат", 20),
+    ("тридцат", 30),
+    ("сороков", 40),
+    ("пятидесят", 50),
+    ("шестидесят", 60),
+    ("семидесят", 70),
+    ("восьмидесят", 80),
+    ("девяност", 90),
+    ("сот", 100),
+    ("тысячн", 1000),
+];
+const RU_ADJECTIVE_ENDINGS: &[&str] = &[
+    "ый", "ой", "ий", "ая", "ое", "ого", "ому", "ом", "ую", "ые", "ых", "ым", "ыми",
+];
+const RU_TRETIY_ENDINGS: &[&str] = &[
+    "ий", "ья", "ье", "ьего", "ьей", "ьему", "ьем", "ью", "ьи", "ьих",
+];
+const RU_MONTHS: &[&str] = &[
+    "января",
+    "февраля",
+    "марта",
+    "апреля",
+    "мая",
+    "июня",
+    "июля",
+    "августа",
+    "сентября",
+    "октября",
+    "ноября",
+    "декабря",
+];
+
+// Unit words -> symbol written after the number
+const EN_UNIT_SYMBOLS: &[(&[&str], &str)] = &[
+    (
+        &["kilometer", "kilometers", "kilometre", "kilometres"],
+        "km",
+    ),
+    (&["meter", "meters", "metre", "metres"], "m"),
+    (
+        &["centimeter", "centimeters", "centimetre", "centimetres"],
+        "cm",
+    ),
+    (
+        &["millimeter", "millimeters", "millimetre", "millimetres"],
+        "mm",
+    ),
+    (&["kilogram", "kilograms", "kilo", "kilos"], "kg"),
+    (&["gram", "grams"], "g"),
+    (&["liter", "liters", "litre", "litres"], "l"),
+    (
+        &["milliliter", "milliliters", "millilitre", "millilitres"],
+        "ml",
+    ),
+    (&["mile", "miles"], "mi"),
+    (&["kilobyte", "kilobytes"], "KB"),
+    (&["megabyte", "megabytes"], "MB"),
+    (&["gigabyte", "gigabytes"], "GB"),
+    (&["terabyte", "terabytes"], "TB"),
+    (&["hertz"], "Hz"),
+    (&["kilohertz"], "kHz"),
+    (&["megahertz"], "MHz"),
+    (&["gigahertz"], "GHz"),
+];
+const RU_UNIT_SYMBOLS: &[(&[&str], &str)] = &[
+    (&["километр", "километра", "километров", "километрах"], "км"),
+    (&["метр", "метра", "метров", "метрах"], "м"),
+    (&["сантиметр", "сантиметра", "сантиметров"], "см"),
+    (&["миллиметр", "миллиметра", "миллиметров"], "мм"),
+    (&["килограмм", "килограмма", "килограммов"], "кг"),
+    (&["грамм", "грамма", "граммов"], "г"),
+    (&["литр", "литра", "литров"], "л"),
+    (&["килобайт", "килобайта", "килобайтов"], "КБ"),
+    (&["мегабайт", "мегабайта", "мегабайтов"], "МБ"),
+    (&["гигабайт", "гигабайта", "гигабайтов"], "ГБ"),
+    (&["терабайт", "терабайта", "терабайтов"], "ТБ"),
+];
+
+// A word with the punctuation around it: "(twenty," -> "(", "twenty", ","
+struct Token<'a> {
+    lead: &'a str,
+    core: &'a str,
+    // Lowercase core with typographic apostrophes normalized
+    word: String,
+    trail: &'a str,
+    // Written joined to the next token with a hyphen ("twenty-five")
+    hyphen_next: bool,
+}
+
+impl Token<'_> {
+    fn original(&self) -> String {
+        format!("{}{}{}", self.lead, self.core, self.trail)
+    }
+}
+
+// A number read at some position
+struct NumberMatch {
+    value: u64,
+    // Digits after the decimal point
+    decimals: Option<String>,
+    ordinal: bool,
+    // Tokens consumed
+    len: usize,
+    // Number words among them (0 when the number was already written in digits)
+    words: usize,
+}
+
+impl NumberMatch {
+    // Spelled-out numbers from ten up, compound numbers and decimals read better as digits
+    fn worth_converting(&self) -> bool {
+        self.words > 0 && (self.value >= 10 || self.words > 1 || self.decimals.is_some())
+    }
+}
+
+/// Inverse text normalization for English and Russian: spoken numbers, dates, times,
+/// percentages, money and units written with digits and symbols
+/// ("twenty five percent" -> "25%", "третьего марта" -> "3 марта")
+pub struct InverseNormalization {
+    lang: Option<Lang>,
+}
+
+impl InverseNormalization {
+    pub fn for_language(language: &str) -> Self {
+        let lang = match language.get(..2).unwrap_or(language) {
+            "en" => Some(Lang::English),
+            "ru" => Some(Lang::Russian),
+            _ => None,
+        };
+        Self { lang }
+    }
+}
+
+impl TextStage for InverseNormalization {
+    fn apply(&self, text: &str) -> String {
+        match self.lang {
+            Some(lang) => text
+                .lines()
+                .map(|line| normalize_line(lang, line))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// Whether `word` (lowercase) is a number word of `language` ("five", "пятого")
+pub fn is_number_word(language: &str, word: &str) -> bool {
+    match language.get(..2).unwrap_or(language) {
+        "en" => lookup_en(word).is_some(),
+        "ru" => lookup_ru(word).is_some(),
+        _ => false,
+    }
+}
+
+fn tokenize(lang: Lang, line: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for raw in line.split_whitespace() {
+        let Some(start) = raw.find(char::is_alphanumeric) else {
+            tokens.push(Token {
+                lead: raw,
+                core: "",
+                word: String::new(),
+                trail: "",
+                hyphen_next: false,
+            });
+            continue;
+        };
+        let end = raw
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_alphanumeric())
+            .map_or(raw.len(), |(i, c)| i + c.len_utf8());
+        let (lead, core, trail) = (&raw[..start], &raw[start..end], &raw[end..]);
+
+        // "twenty-five" is read as two number words
+        let parts: Vec<&str> = core.split('-').collect();
+        if parts.len() > 1
+            && parts
+                .iter()
+                .all(|p| lookup(lang, &p.to_lowercase()).is_some())
+        {
+            let last = parts.len() - 1;
+            for (n, part) in parts.iter().enumerate() {
+                tokens.push(Token {
+                    lead: if n == 0 { lead } else { "" },
+                    core: part,
+                    word: part.to_lowercase(),
+                    trail: if n == last { trail } else { "" },
+                    hyphen_next: n != last,
+                });
+            }
+            continue;
+        }
+        tokens.push(Token {
+            lead,
+            core,
+            word: core.to_lowercase().replace('’', "'"),
+            trail,
+            hyphen_next: false,
+        });
+    }
+    tokens
+}
+
+fn lookup(lang: Lang, word: &str) -> Option<NumWord> {
+    match lang {
+        Lang::English => lookup_en(word),
+        Lang::Russian => lookup_ru(word),
+    }
+}
+
+fn lookup_en(word: &str) -> Option<NumWord> {
+    let index = |list: &[&str]| list.iter().position(|w| *w == word).map(|i| i as u64);
+    if let Some(i) = index(EN_UNITS) {
+        return Some(NumWord::Unit(i));
+    }
+    if let Some(i) = index(EN_TEENS) {
+        return Some(NumWord::Teen(10 + i));
+    }
+    if let Some(i) = index(EN_TENS) {
+        return Some(NumWord::Tens(20 + 10 * i));
+    }
+    match word {
+        "hundred" => return Some(NumWord::Hundred),
+        "thousand" => return Some(NumWord::Scale(1_000)),
+        "million" => return Some(NumWord::Scale(1_000_000)),
+        "billion" => return Some(NumWord::Scale(1_000_000_000)),
+        _ => {}
+    }
+    EN_ORDINALS
+        .iter()
+        .find(|(w, _)| *w == word)
+        .map(|(_, v)| NumWord::Ordinal(*v))
+}
+
+fn lookup_ru(word: &str) -> Option<NumWord> {
+    if let Some((_, value)) = RU_NUMBERS.iter().find(|(forms, _)| forms.contains(&word)) {
+        return Some(match value {
+            0..=9 => NumWord::Unit(*value),
+            10..=19 => NumWord::Teen(*value),
+            20..=90 => NumWord::Tens(*value),
+            _ => NumWord::Hundreds(*value),
+        });
+    }
+    if let Some((_, scale)) = RU_SCALES.iter().find(|(stem, _)| {
+        word.strip_prefix(stem).is_some_and(|rest| {
+            ["", "а", "и", "у", "ов", "ам", "ами", "ах", "ей", "ею"].contains(&rest)
+        })
+    }) {
+        return Some(NumWord::Scale(*scale));
+    }
+    if word
+        .strip_prefix("трет")
+        .is_some_and(|rest| RU_TRETIY_ENDINGS.contains(&rest))
+    {
+        return Some(NumWord::Ordinal(3));
+    }
+    RU_ORDINAL_STEMS
+        .iter()
+        .find(|(stem, _)| {
+            word.strip_prefix(stem)
+                .is_some_and(|rest| RU_ADJECTIVE_ENDINGS.contains(&rest))
+        })
+        .map(|(_, v)| NumWord::Ordinal(*v))
+}
+
+// A number already written in digits: "25", "3.5", "2,5"
+fn parse_digits(word: &str) -> Option<(u64, Option<String>)> {
+    let (int, frac) = match word.find(['.', ',']) {
+        Some(i) => (&word[..i], Some(&word[i + 1..])),
+        None => (word, None),
+    };
+    if int.is_empty() || !int.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    if let Some(frac) = frac {
+        if frac.is_empty() || !frac.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+    }
+    Some((int.parse().ok()?, frac.map(|f| f.to_string())))
+}
+
+// Read a number starting at `start`; stops at punctuation after a word
+fn parse_number(lang: Lang, tokens: &[Token], start: usize) -> Option<NumberMatch> {
+    let first = tokens.get(start)?;
+    if let Some((value, decimals)) = parse_digits(&first.word) {
+        return Some(NumberMatch {
+            value,
+            decimals,
+            ordinal: false,
+            len: 1,
+            words: 0,
+        });
+    }
+
+    let (mut total, mut current) = (0u64, 0u64);
+    let mut last: Option<NumWord> = None;
+    let mut last_scale = u64::MAX;
+    let mut ordinal = false;
+    let mut len = 0;
+    let mut words = 0;
+
+    while let Some(token) = tokens.get(start + len) {
+        let next_word = tokens.get(start + len + 1).map(|t| t.word.as_str());
+        let kind = match lookup(lang, &token.word) {
+            Some(kind) => kind,
+            // "a hundred", "a thousand"
+            None if lang == Lang::English && token.word == "a" && len == 0 => {
+                match next_word.and_then(lookup_en) {
+                    Some(NumWord::Hundred) | Some(NumWord::Scale(_)) => {
+                        current = 1;
+                        last = Some(NumWord::Unit(1));
+                        len += 1;
+                        continue;
+                    }
+                    _ => break,
+                }
+            }
+            // "one hundred and five"
+            None if lang == Lang::English
+                && token.word == "and"
+                && (total > 0 || current >= 100) =>
+            {
+                match next_word.and_then(lookup_en) {
+                    Some(
+                        NumWord::Unit(_)
+                        | NumWord::Teen(_)
+                        | NumWord::Tens(_)
+                        | NumWord::Ordinal(_),
+                    ) => {
+                        len += 1;
+                        continue;
+                    }
+                    _ => break,
+                }
+            }
+            None => break,
+        };
+
+        let small = matches!(last, Some(NumWord::Unit(_) | NumWord::Teen(_)));
+        match kind {
+            NumWord::Unit(v) | NumWord::Teen(v) | NumWord::Ordinal(v) if v < 100 => {
+                let is_tens = v >= 20;
+                if small
+                    || (is_tens && (matches!(last, Some(NumWord::Tens(_))) || current % 100 != 0))
+                {
+                    break;
+                }
+                if v >= 10 && matches!(last, Some(NumWord::Tens(_))) {
+                    break;
+                }
+                current += v;
+                ordinal = matches!(kind, NumWord::Ordinal(_));
+            }
+            NumWord::Tens(v) => {
+                if small || matches!(last, Some(NumWord::Tens(_))) || current % 100 != 0 {
+                    break;
+                }
+                current += v;
+            }
+            NumWord::Hundreds(v) => {
+                if current != 0 {
+                    break;
+                }
+                current = v;
+            }
+            NumWord::Hundred => {
+                if current == 0 || current >= 100 {
+                    break;
+                }
+                current *= 100;
+            }
+            NumWord::Scale(scale) | NumWord::Ordinal(scale) => {
+                if scale >= last_scale || (current == 0 && total > 0) {
+                    break;
+                }
+                let ordinal_scale = matches!(kind, NumWord::Ordinal(_));
+                if ordinal_scale && scale == 100 {
+                    current = current.max(1) * 100;
+                } else {
+                    total += current.max(1) * scale;
+                    current = 0;
+                    last_scale = scale;
+                }
+                ordinal = ordinal_scale;
+            }
+            NumWord::Unit(_) | NumWord::Teen(_) => unreachable!("small numbers are handled above"),
+        }
+        last = Some(kind);
+        len += 1;
+        words += 1;
+        if ordinal || !token.trail.is_empty() {
+            break;
+        }
+    }
+
+    if words == 0 {
+        return None;
+    }
+    // Trailing "and"/"a" that didn't lead to a number
+    while len > 0 && lookup(lang, &tokens[start + len - 1].word).is_none() {
+        len -= 1;
+    }
+    let mut number = NumberMatch {
+        value: total + current,
+        decimals: None,
+        ordinal,
+        len,
+        words,
+    };
+
+    // "three point five"
+    if lang == Lang::English && !ordinal && tokens[start + len - 1].trail.is_empty() {
+        if let Some(point) = tokens.get(start + len).filter(|t| t.word == "point") {
+            let mut digits = String::new();
+            let mut n = start + len + 1;
+            if point.trail.is_empty() {
+                while let Some(token) = tokens.get(n) {
+                    match lookup_en(&token.word) {
+                        Some(NumWord::Unit(d)) => digits.push_str(&d.to_string()),
+                        _ if token.word == "oh" => digits.push('0'),
+                        _ => break,
+                    }
+                    n += 1;
+                    if !token.trail.is_empty() {
+                        break;
+                    }
+                }
+            }
+            if !digits.is_empty() {
+                number.words += n - (start + len);
+                number.len = n - start;
+                number.decimals = Some(digits);
+            }
+        }
+    }
+    Some(number)
+}
+
+fn group_digits(value: u64, separator: &str) -> String {
+    let digits = value.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push_str(separator);
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn format_number(lang: Lang, number: &NumberMatch) -> String {
+    let int = match lang {
+        Lang::English if number.value >= 1_000 => group_digits(number.value, ","),
+        // Russian groups from five digits on, with a non-breaking space
+        Lang::Russian if number.value >= 10_000 => group_digits(number.value, "\u{a0}"),
+        _ => number.value.to_string(),
+    };
+    match &number.decimals {
+        Some(decimals) if lang == Lang::Russian => format!("{},{}", int, decimals),
+        Some(decimals) => format!("{}.{}", int, decimals),
+        None => int,
+    }
+}
+
+fn en_ordinal_suffix(value: u64) -> &'static str {
+    match (value % 10, value % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// A conversion: tokens consumed from the current position and the text replacing them
+type Conversion = Option<(usize, String)>;
+
+fn normalize_line(lang: Lang, line: &str) -> String {
+    let tokens = tokenize(lang, line);
+    let mut out = String::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let converted = match lang {
+            Lang::English => en_time(&tokens, i)
+                .or_else(|| en_date(&tokens, i))
+                .or_else(|| number_with_suffix(lang, &tokens, i)),
+            Lang::Russian => ru_time(&tokens, i)
+                .or_else(|| ru_date(&tokens, i))
+                .or_else(|| number_with_suffix(lang, &tokens, i)),
+        };
+        let (len, text, hyphen_next) = match converted {
+            Some((len, text)) => {
+                let last = &tokens[i + len - 1];
+                let text = format!("{}{}{}", tokens[i].lead, text, last.trail);
+                (len, text, false)
+            }
+            None => (1, tokens[i].original(), tokens[i].hyphen_next),
+        };
+        out.push_str(&text);
+        i += len;
+        if i < tokens.len() {
+            out.push(if hyphen_next { '-' } else { ' ' });
+        }
+    }
+    out
+}
+
+// Number followed by percent, currency or unit words; otherwise a plain cardinal or ordinal
+fn number_with_suffix(lang: Lang, tokens: &[Token], i: usize) -> Conversion {
+    // "nineteen ninety nine", "twenty twenty four" are read as years
+    let two_groups = parse_number(lang, tokens, i).is_some_and(|n| n.value < 100);
+    if lang == Lang::English && two_groups {
+        if let Some((len, year)) = en_year(tokens, i).filter(|(_, y)| (1900..2100).contains(y)) {
+            if len > 1 {
+                return Some((len, year.to_string()));
+            }
+        }
+    }
+    let number = parse_number(lang, tokens, i)?;
+    let after = i + number.len;
+    let open = tokens[after - 1].trail.is_empty();
+    let word_at = |n: usize| tokens.get(n).filter(|_| open).map(|t| t.word.as_str());
+    let formatted = format_number(lang, &number);
+
+    if !number.ordinal {
+        match (lang, word_at(after)) {
+            (Lang::English, Some("percent"))
+            | (Lang::Russian, Some("процент" | "процента" | "процентов" | "процентах")) =>
+            {
+                return Some((number.len + 1, format!("{}%", formatted)));
+            }
+            (Lang::English, Some("per"))
+                if tokens.get(after + 1).is_some_and(|t| t.word == "cent")
+                    && tokens[after].trail.is_empty() =>
+            {
+                return Some((number.len + 2, format!("{}%", formatted)));
+            }
+            _ => {}
+        }
+        let suffix = open
+            .then(|| money(lang, tokens, after, &number, &formatted))
+            .flatten()
+            .or_else(|| {
+                open.then(|| unit(lang, tokens, after, &formatted))
+                    .flatten()
+            });
+        if let Some((len, text)) = suffix {
+            return Some((number.len + len, text));
+        }
+    }
+
+    if !number.worth_converting()
+        || (lang == Lang::Russian
+            && number.len == 1
+            && RU_AMBIGUOUS.contains(&tokens[i].word.as_str()))
+    {
+        return None;
+    }
+    match (lang, number.ordinal) {
+        (Lang::English, true) => Some((
+            number.len,
+            format!("{}{}", formatted, en_ordinal_suffix(number.value)),
+        )),
+        // Russian ordinals are only written as digits in dates and years
+        (Lang::Russian, true) => {
+            match tokens.get(after).filter(|_| open).map(|t| t.word.as_str()) {
+                Some("год" | "года" | "году" | "годом") if number.value >= 1000 => {
+                    Some((number.len, number.value.to_string()))
+                }
+                _ => None,
+            }
+        }
+        (_, false) => Some((number.len, formatted)),
+    }
+}
+
+// "five dollars (and) fifty cents" -> "$5.50", "сто рублей" -> "100 ₽"
+fn money(
+    lang: Lang,
+    tokens: &[Token],
+    at: usize,
+    number: &NumberMatch,
+    formatted: &str,
+) -> Option<(usize, String)> {
+    let currency = tokens.get(at)?;
+    let symbol = match (lang, currency.word.as_str()) {
+        (Lang::English, "dollar" | "dollars" | "bucks") => "$",
+        (Lang::English, "euro" | "euros") => "€",
+        (Lang::English, "yen") => "¥",
+        (Lang::Russian, "рубль" | "рубля" | "рублей") => "₽",
+        (Lang::Russian, "доллар" | "доллара" | "долларов") => "$",
+        (Lang::Russian, "евро") => "€",
+        _ => return None,
+    };
+    let mut len = 1;
+    let mut amount = formatted.to_string();
+
+    // Cents/kopecks after the main unit
+    if currency.trail.is_empty() && number.decimals.is_none() {
+        let mut n = at + 1;
+        if lang == Lang::English
+            && tokens
+                .get(n)
+                .is_some_and(|t| t.word == "and" && t.trail.is_empty())
+        {
+            n += 1;
+        }
+        if let Some(cents) = parse_number(lang, tokens, n)
+            .filter(|c| c.value < 100 && !c.ordinal && c.decimals.is_none())
+        {
+            let unit_at = n + cents.len;
+            let is_cents = tokens[unit_at - 1].trail.is_empty()
+                && tokens.get(unit_at).is_some_and(|t| match lang {
+                    Lang::English => matches!(t.word.as_str(), "cent" | "cents"),
+                    Lang::Russian => matches!(t.word.as_str(), "копейка" | "копейки" | "копеек"),
+                });
+            if is_cents {
+                let separator = if lang == Lang::Russian { "," } else { "." };
+                amount = format!("{}{}{:02}", amount, separator, cents.value);
+                len = unit_at + 1 - at;
+            }
+        }
+    }
+
+    let text = match lang {
+        Lang::English => format!("{}{}", symbol, amount),
+        Lang::Russian => format!("{}\u{a0}{}", amount, symbol),
+    };
+    Some((len, text))
+}
+
+// "five kilometers" -> "5 km", "twenty degrees celsius" -> "20°C"
+fn unit(lang: Lang, tokens: &[Token], at: usize, formatted: &str) -> Option<(usize, String)> {
+    let word = tokens.get(at)?.word.as_str();
+    let degrees = match lang {
+        Lang::English => matches!(word, "degree" | "degrees"),
+        Lang::Russian => matches!(word, "градус" | "градуса" | "градусов" | "градусах"),
+    };
+    if degrees {
+        let scale = tokens
+            .get(at + 1)
+            .filter(|_| tokens[at].trail.is_empty())
+            .and_then(|t| match t.word.as_str() {
+                "celsius" | "цельсия" => Some("C"),
+                "fahrenheit" | "фаренгейта" => Some("F"),
+                _ => None,
+            });
+        return Some(match scale {
+            Some(scale) => (2, format!("{}°{}", formatted, scale)),
+            None => (1, format!("{}°", formatted)),
+        });
+    }
+    let table = match lang {
+        Lang::English => EN_UNIT_SYMBOLS,
+        Lang::Russian => RU_UNIT_SYMBOLS,
+    };
+    table
+        .iter()
+        .find(|(words, _)| words.contains(&word))
+        .map(|(_, symbol)| (1, format!("{}\u{a0}{}", formatted, symbol)))
+}
+
+// Year after a date: "two thousand twenty four", "twenty twenty four", "nineteen ninety nine"
+fn en_year(tokens: &[Token], at: usize) -> Option<(usize, u64)> {
+    let first = parse_number(Lang::English, tokens, at)?;
+    if first.ordinal || first.decimals.is_some() {
+        return None;
+    }
+    if (1000..3000).contains(&first.value) {
+        return Some((first.len, first.value));
+    }
+    if first.words == 1
+        && (10..=29).contains(&first.value)
+        && tokens[at + first.len - 1].trail.is_empty()
+    {
+        let second = parse_number(Lang::English, tokens, at + first.len)?;
+        if second.words > 0 && !second.ordinal && (1..100).contains(&second.value) {
+            return Some((first.len + second.len, first.value * 100 + second.value));
+        }
+    }
+    None
+}
+
+fn en_month(token: &Token) -> Option<usize> {
+    let month = EN_MONTHS.iter().position(|m| *m == token.word)?;
+    // "march" and "may" are months only when capitalized ("they march twenty miles")
+    (!matches!(month, 2 | 4) || token.core.starts_with('M')).then_some(month)
+}
+
+// "March third", "March 3rd twenty twenty four", "the third of March"
+fn en_date(tokens: &[Token], i: usize) -> Conversion {
+    let (month, day, len) = if let Some(month) = en_month(&tokens[i]) {
+        if !tokens[i].trail.is_empty() {
+            return None;
+        }
+        let day = parse_number(Lang::English, tokens, i + 1).filter(|d| d.words > 0)?;
+        // "March twenty twenty four" is a month and year
+        if !day.ordinal {
+            if let Some((year_len, year)) = en_year(tokens, i + 1).filter(|(l, _)| *l > day.len) {
+                let text = format!("{} {}", capitalize(EN_MONTHS[month]), year);
+                return Some((1 + year_len, text));
+            }
+        }
+        let len = 1 + day.len;
+        (month, day, len)
+    } else {
+        let start = if tokens[i].word == "the" && tokens[i].trail.is_empty() {
+            i + 1
+        } else {
+            i
+        };
+        let day = parse_number(Lang::English, tokens, start).filter(|d| d.ordinal)?;
+        let of = start + day.len;
+        if tokens
+            .get(of)
+            .is_none_or(|t| t.word != "of" || !t.trail.is_empty())
+            || !tokens[of - 1].trail.is_empty()
+        {
+            return None;
+        }
+        let month = tokens.get(of + 1).and_then(en_month)?;
+        let len = of + 2 - i;
+        (month, day, len)
+    };
+    if !(1..=31).contains(&day.value) || day.decimals.is_some() {
+        return None;
+    }
+
+    let mut text = format!("{} {}", capitalize(EN_MONTHS[month]), day.value);
+    let mut len = len;
+    if tokens[i + len - 1].trail.is_empty() || tokens[i + len - 1].trail == "," {
+        if let Some((year_len, year)) = en_year(tokens, i + len) {
+            text = format!("{}, {}", text, year);
+            len += year_len;
+        }
+    }
+    Some((len, text))
+}
+
+// "three thirty pm" -> "3:30 PM", "seven o'clock" -> "7:00"
+fn en_time(tokens: &[Token], i: usize) -> Conversion {
+    let hour = parse_number(Lang::English, tokens, i)?;
+    if hour.ordinal || hour.decimals.is_some() || !(1..=12).contains(&hour.value) {
+        return None;
+    }
+    let mut n = i + hour.len;
+    let mut minutes = None;
+    if tokens[n - 1].trail.is_empty() {
+        if tokens
+            .get(n)
+            .is_some_and(|t| t.word == "oh" && t.trail.is_empty())
+        {
+            let m = parse_number(Lang::English, tokens, n + 1)
+                .filter(|m| m.value < 10 && m.words == 1)?;
+            minutes = Some(m.value);
+            n += 1 + m.len;
+        } else if let Some(m) = parse_number(Lang::English, tokens, n).filter(|m| {
+            m.words > 0 && !m.ordinal && m.decimals.is_none() && (10..60).contains(&m.value)
+        }) {
+            minutes = Some(m.value);
+            n += m.len;
+        }
+    }
+    if !tokens[n - 1].trail.is_empty() {
+        return None;
+    }
+    let marker = tokens.get(n)?;
+    let marker_word = marker.word.replace('.', "");
+    let text = match (marker_word.as_str(), minutes) {
+        ("am" | "pm", Some(m)) => format!("{}:{:02} {}", hour.value, m, marker_word.to_uppercase()),
+        ("am" | "pm", None) => format!("{} {}", hour.value, marker_word.to_uppercase()),
+        ("o'clock", None) => format!("{}:00", hour.value),
+        _ => return None,
+    };
+    Some((n + 1 - i, text))
+}
+
+// "третьего марта" -> "3 марта", "первое мая две тысячи двадцать пятого года" -> "1 мая 2025 года"
+fn ru_date(tokens: &[Token], i: usize) -> Conversion {
+    let day = parse_number(Lang::Russian, tokens, i)?;
+    if !(1..=31).contains(&day.value) || day.decimals.is_some() {
+        return None;
+    }
+    // Digits are fine too: "3 марта" stays, but a following spoken year still converts
+    if !day.ordinal && day.words > 0 {
+        return None;
+    }
+    let month_at = i + day.len;
+    if !tokens[month_at - 1].trail.is_empty() {
+        return None;
+    }
+    let month = tokens
+        .get(month_at)
+        .filter(|t| RU_MONTHS.contains(&t.word.as_str()))?;
+    let mut text = format!("{} {}", day.value, month.core);
+    let mut len = day.len + 1;
+
+    if month.trail.is_empty() {
+        if let Some(year) = parse_number(Lang::Russian, tokens, month_at + 1)
+            .filter(|y| y.ordinal && y.value >= 1000)
+        {
+            let year_word = tokens
+                .get(month_at + 1 + year.len)
+                .filter(|_| tokens[month_at + year.len].trail.is_empty())
+                .filter(|t| matches!(t.word.as_str(), "год" | "года" | "году"));
+            if let Some(year_word) = year_word {
+                text = format!("{} {} {}", text, year.value, year_word.core);
+                len += year.len + 1;
+            }
+        }
+    }
+    (day.words > 0 || len > day.len + 1).then_some((len, text))
+}
+
+// "в три часа" -> "в 3:00", "в девять часов тридцать минут" -> "в 9:30"
+fn ru_time(tokens: &[Token], i: usize) -> Conversion {
+    if !matches!(tokens[i].word.as_str(), "в" | "во") || !tokens[i].trail.is_empty() {
+        return None;
+    }
+    let hour = parse_number(Lang::Russian, tokens, i + 1).filter(|h| h.words > 0 && !h.ordinal)?;
+    if hour.value > 23 || hour.decimals.is_some() {
+        return None;
+    }
+    let hours_at = i + 1 + hour.len;
+    let hours_word = tokens
+        .get(hours_at)
+        .filter(|_| tokens[hours_at - 1].trail.is_empty())?;
+    if !matches!(hours_word.word.as_str(), "час" | "часа" | "часов") {
+        return None;
+    }
+    let mut len = hours_at + 1 - i;
+    let mut minutes = 0;
+    if hours_word.trail.is_empty() {
+        if let Some(m) =
+            parse_number(Lang::Russian, tokens, hours_at + 1).filter(|m| m.value < 60 && !m.ordinal)
+        {
+            let minutes_at = hours_at + 1 + m.len;
+            let is_minutes = tokens[minutes_at - 1].trail.is_empty()
+                && tokens.get(minutes_at).is_some_and(|t| {
+                    matches!(t.word.as_str(), "минута" | "минуты" | "минут" | "минуту")
+                });
+            if is_minutes {
+                minutes = m.value;
+                len = minutes_at + 1 - i;
+            }
+        }
+    }
+    Some((
+        len,
+        format!("{} {}:{:02}", tokens[i].core, hour.value, minutes),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(language: &str, cases: &[(&str, &str)]) {
+        let stage = InverseNormalization::for_language(language);
+        for (input, expected) in cases {
+            assert_eq!(stage.apply(input), *expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn english_numbers() {
+        check(
+            "en",
+            &[
+                ("twenty five", "25"),
+                ("one hundred and twenty three", "123"),
+                ("two thousand five hundred", "2,500"),
+                ("two point five", "2.5"),
+                ("the twenty-first century", "the 21st century"),
+                ("I have three apples", "I have three apples"),
+                ("one of them", "one of them"),
+            ],
+        );
+    }
+
+    #[test]
+    fn english_suffixes() {
+        check(
+            "en",
+            &[
+                ("twenty five percent", "25%"),
+                ("five per cent", "5%"),
+                ("five dollars and fifty cents", "$5.50"),
+                ("twelve kilometers", "12\u{a0}km"),
+                ("it is twenty degrees celsius", "it is 20°C"),
+            ],
+        );
+    }
+
+    #[test]
+    fn english_dates_and_times() {
+        check(
+            "en",
+            &[
+                ("March third twenty twenty four", "March 3, 2024"),
+                ("the third of March", "March 3"),
+                ("on May fifth", "on May 5"),
+                ("nineteen ninety nine", "1999"),
+                ("three thirty pm", "3:30 PM"),
+                ("seven o'clock", "7:00"),
+            ],
+        );
+    }
+
+    #[test]
+    fn english_false_positives() {
+        check(
+            "en",
+            &[
+                ("they march twenty miles", "they march 20\u{a0}mi"),
+                ("we may go twenty miles", "we may go 20\u{a0}mi"),
+                ("wait a second", "wait a second"),
+                ("the first of them", "the first of them"),
+            ],
+        );
+    }
+
+    #[test]
+    fn russian_numbers_and_dates() {
+        check(
+            "ru",
+            &[
+                ("сорок два", "42"),
+                ("двадцать пять процентов", "25%"),
+                ("сто рублей", "100\u{a0}₽"),
+                ("пять килограммов", "5\u{a0}кг"),
+                ("третьего марта", "3 марта"),
+                (
+                    "первое мая две тысячи двадцать пятого года",
+                    "1 мая 2025 года",
+                ),
+                ("в девять часов тридцать минут", "в 9:30"),
+            ],
+        );
+    }
+
+    #[test]
+    fn russian_false_positives() {
+        check(
+            "ru",
+            &[
+                ("я люблю свою семью", "я люблю свою семью"),
+                ("сорока украла кольцо", "сорока украла кольцо"),
+                ("сорока процентов", "40%"),
+            ],
+        );
+    }
+
+    #[test]
+    fn other_languages_unchanged() {
+        check("de", &[("zwanzig Prozent", "zwanzig Prozent")]);
+    }
+}
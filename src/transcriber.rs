@@ -0,0 +1,35 @@
+/// A speech-to-text engine. Samples are raw captured audio (any rate, interleaved channels);
+/// implementations convert them to whatever their backend needs.
+pub trait Transcriber: Send + Sync {
+    /// Transcribe speech in `language` (2-letter code, auto-detected when None)
+    fn transcribe_samples(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        language: Option<&str>,
+    ) -> Result<String, String>;
+
+    /// `transcribe_samples` with text that precedes the speech, so names and style carry over.
+    /// Engines that can't take a prompt ignore it.
+    fn transcribe_with_prompt(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        language: Option<&str>,
+        prompt: Option<&str>,
+    ) -> Result<String, String> {
+        let _ = prompt;
+        self.transcribe_samples(samples, sample_rate, channels, language)
+    }
+
+    /// Translate speech to English
+    fn translate_samples(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        language: Option<&str>,
+    ) -> Result<String, String>;
+}
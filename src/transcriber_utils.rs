@@ -1,123 +1,252 @@
-use std::sync::{Arc, Mutex};
+use lazy_static::lazy_static;
+use std::sync::{Arc, Mutex, MutexGuard};
 
+use crate::compute_policy;
 use crate::config;
+use crate::local_models;
+use crate::model_cache::{ModelCache, ModelKey};
+use crate::model_manager;
+use crate::model_registry;
+use crate::post_processing;
+use crate::remote_transcriber::{self, RemoteError, RemoteTranscriber};
+use crate::transcriber::Transcriber;
 use crate::whisper::WhisperTranscriber;
 
+lazy_static! {
+    // Serializes transcriptions from hotkey dictation and the local API so they don't compete
+    static ref TRANSCRIPTION_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Hold this while transcribing; other transcriptions wait until it is dropped
+pub fn transcription_slot() -> MutexGuard<'static, ()> {
+    TRANSCRIPTION_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 /// Select the model filename based on selected model and language mode.
 pub fn select_model_file(selected_model: &str, is_english: bool) -> String {
-    match (selected_model, is_english) {
-        ("large", true) => "ggml-large-v2.bin".to_string(),
-        ("large", false) => "ggml-large-v2.bin".to_string(),
-        (m @ ("base" | "small" | "medium"), true) => format!("ggml-{m}.en.bin"),
-        (m @ ("base" | "small" | "medium"), false) => format!("ggml-{m}.bin"),
-        // Fallbacks to base variants
-        (_, true) => "ggml-base.en.bin".to_string(),
-        (_, false) => "ggml-base.bin".to_string(),
+    let (en, multi) = local_models::model_files(selected_model);
+    if is_english {
+        en
+    } else {
+        multi
     }
 }
 
-/// Ensure the appropriate WhisperTranscriber is initialized (English or multilingual),
-/// with fallback to base models if the selected file is missing.
-pub fn ensure_transcriber_for(
-    is_english: bool,
-    model_file: &str,
-    english_transcriber: &Arc<Mutex<Option<WhisperTranscriber>>>,
-    multilingual_transcriber: &Arc<Mutex<Option<WhisperTranscriber>>>,
-) {
-    // If the selected model is missing, fall back to base automatically
-    let resolved_model = if config::get_model_path(model_file).is_some() {
-        model_file.to_string()
-    } else {
+/// Like `select_model_file`, but on battery the configured battery model is used instead
+/// when its file is already on disk (it is never downloaded just for that).
+pub fn select_model_file_for_power(selected_model: &str, is_english: bool) -> String {
+    if let Some(battery_model) = compute_policy::battery_model() {
+        let battery_file = select_model_file(&battery_model, is_english);
+        if config::get_model_path(&battery_file).is_some() {
+            return battery_file;
+        }
+    }
+    select_model_file(selected_model, is_english)
+}
+
+/// The best registry model already on disk for the language: the largest one,
+/// preferring English-only files for English. Used while the selected model downloads.
+pub fn fallback_model_file(is_english: bool) -> Option<String> {
+    model_registry::MODELS
+        .iter()
+        .filter(|m| is_english || !m.english_only)
+        .filter(|m| config::get_model_path(m.file_name).is_some())
+        .max_by_key(|m| (m.size_mb, is_english && m.english_only))
+        .map(|m| m.file_name.to_string())
+}
+
+/// The model file that will actually be used for `model_file`: the file itself if present,
+/// otherwise the best model on disk, otherwise the base model (downloaded on demand).
+pub fn resolve_model_file(model_file: &str, is_english: bool) -> String {
+    if config::get_model_path(model_file).is_some() {
+        return model_file.to_string();
+    }
+    fallback_model_file(is_english).unwrap_or_else(|| {
         if is_english {
-            "ggml-base.en.bin".to_string()
+            model_registry::FALLBACK_ENGLISH_FILE.to_string()
         } else {
-            "ggml-base.bin".to_string()
+            model_registry::FALLBACK_MULTILINGUAL_FILE.to_string()
         }
-    };
+    })
+}
 
-    if is_english {
-        let mut guard = english_transcriber.lock().unwrap();
-        if guard.is_none() {
-            println!(
-                "Initializing English transcriber with model: {}",
-                resolved_model
-            );
-            match WhisperTranscriber::new(&resolved_model) {
-                Ok(t) => *guard = Some(t),
-                Err(e) => {
-                    eprintln!(
-                        "Failed to initialize English WhisperTranscriber with model {}: {}",
-                        resolved_model, e
-                    );
-                    eprintln!("English transcription will be disabled");
+/// Ensure the WhisperTranscriber for `model_file` is loaded in the model cache
+/// (English or multilingual), falling back to a model on disk if the selected file is missing.
+pub fn ensure_transcriber_for(
+    is_english: bool,
+    model_file: &str,
+    cache: &ModelCache,
+) -> Result<Arc<WhisperTranscriber>, String> {
+    let resolved_model = resolve_model_file(model_file, is_english);
+    if resolved_model != model_file {
+        println!(
+            "Model {} is not available yet; using {} meanwhile",
+            model_file, resolved_model
+        );
+    }
+
+    model_manager::record_model_use(&resolved_model);
+    let key = ModelKey::for_current_device(&resolved_model);
+    if let Some(t) = cache.get(&key) {
+        return Ok(t);
+    }
+
+    let kind = if is_english { "English" } else { "multilingual" };
+    println!(
+        "Initializing {} transcriber with model: {}",
+        kind, resolved_model
+    );
+    cache.get_or_load(&key).map_err(|e| {
+        format!(
+            "Failed to initialize {} WhisperTranscriber with model {}: {}",
+            kind, resolved_model, e
+        )
+    })
+}
+
+// Remote engine that transcribes with the local model when the server can't be reached
+struct RemoteWithFallback {
+    remote: RemoteTranscriber,
+    fallback: Option<(bool, String, Arc<ModelCache>)>,
+}
+
+impl RemoteWithFallback {
+    fn run(
+        &self,
+        remote: Result<String, RemoteError>,
+        local: impl FnOnce(&WhisperTranscriber) -> Result<String, String>,
+    ) -> Result<String, String> {
+        let fallback = self
+            .fallback
+            .as_ref()
+            .map(|(is_english, model_file, cache)| {
+                move || {
+                    let transcriber = ensure_transcriber_for(*is_english, model_file, cache)?;
+                    local(&transcriber)
                 }
-            }
+            });
+        remote_transcriber::or_local_fallback(remote, fallback)
+    }
+}
+
+impl Transcriber for RemoteWithFallback {
+    fn transcribe_samples(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        language: Option<&str>,
+    ) -> Result<String, String> {
+        let remote = self
+            .remote
+            .request("transcriptions", samples, sample_rate, channels, language);
+        self.run(remote, |t| {
+            t.transcribe_samples(samples, sample_rate, channels, language)
+        })
+    }
+
+    fn translate_samples(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        language: Option<&str>,
+    ) -> Result<String, String> {
+        let remote = self
+            .remote
+            .request("translations", samples, sample_rate, channels, language);
+        self.run(remote, |t| {
+            t.translate_samples(samples, sample_rate, channels, language)
+        })
+    }
+}
+
+/// The engine for the next transcription: the remote server when `engine` is "remote"
+/// (with local fallback if enabled), otherwise the local whisper model from the cache.
+pub fn ensure_engine_for(
+    is_english: bool,
+    model_file: &str,
+    cache: &Arc<ModelCache>,
+) -> Result<Arc<dyn Transcriber>, String> {
+    match config::get_remote_engine() {
+        Some(settings) => {
+            let fallback = settings
+                .fallback_to_local
+                .then(|| (is_english, model_file.to_string(), cache.clone()));
+            Ok(Arc::new(RemoteWithFallback {
+                remote: RemoteTranscriber::new(settings),
+                fallback,
+            }))
         }
-    } else {
-        let mut guard = multilingual_transcriber.lock().unwrap();
-        if guard.is_none() {
-            println!(
-                "Initializing multilingual transcriber with model: {}",
-                resolved_model
-            );
-            match WhisperTranscriber::new(&resolved_model) {
-                Ok(t) => *guard = Some(t),
-                Err(e) => {
-                    eprintln!(
-                        "Failed to initialize Multilingual WhisperTranscriber with model {}: {}",
-                        resolved_model, e
-                    );
-                    eprintln!("Multilingual transcription will be disabled");
-                }
-            }
+        None => {
+            let transcriber: Arc<dyn Transcriber> =
+                ensure_transcriber_for(is_english, model_file, cache)?;
+            Ok(transcriber)
         }
     }
 }
 
-/// Transcribe in-memory audio samples using the provided transcriber reference.
+/// Transcribe in-memory audio samples using the provided transcriber.
 pub fn transcribe_samples_with(
-    transcriber: &Arc<Mutex<Option<WhisperTranscriber>>>,
+    transcriber: &dyn Transcriber,
     samples: &[f32],
     sample_rate: u32,
     channels: u16,
     language: &str,
 ) -> Result<String, String> {
-    let guard = transcriber
-        .lock()
-        .map_err(|_| "Failed to lock transcriber".to_string())?;
-    if let Some(ref t) = *guard {
-        t.transcribe_samples(samples, sample_rate, channels, Some(language))
-            .map_err(|e| format!("Failed to transcribe audio: {}", e))
-    } else {
-        Err("Transcriber is not available".to_string())
-    }
+    transcriber
+        .transcribe_samples(samples, sample_rate, channels, Some(language))
+        .map_err(|e| format!("Failed to transcribe audio: {}", e))
 }
 
-/// Translate in-memory audio samples to English using the provided transcriber reference.
+/// Translate in-memory audio samples to English using the provided transcriber.
 pub fn translate_samples_with(
-    transcriber: &Arc<Mutex<Option<WhisperTranscriber>>>,
+    transcriber: &dyn Transcriber,
     samples: &[f32],
     sample_rate: u32,
     channels: u16,
     language: &str,
 ) -> Result<String, String> {
-    let guard = transcriber
-        .lock()
-        .map_err(|_| "Failed to lock transcriber".to_string())?;
-    if let Some(ref t) = *guard {
-        // language is not strictly necessary for translation; we pass it for symmetry but the method ignores it
-        t.translate_samples(samples, sample_rate, channels, Some(language))
-            .map_err(|e| format!("Failed to translate audio: {}", e))
-    } else {
-        Err("Transcriber is not available".to_string())
-    }
+    // language is not strictly necessary for translation; we pass it for symmetry but the method ignores it
+    transcriber
+        .translate_samples(samples, sample_rate, channels, Some(language))
+        .map_err(|e| format!("Failed to translate audio: {}", e))
 }
 
-/// Explicitly drop the transcriber to free its underlying resources (including GPU VRAM if CUDA is used).
-pub fn cleanup_transcriber(transcriber: &Arc<Mutex<Option<WhisperTranscriber>>>) {
-    if let Ok(mut guard) = transcriber.lock() {
-        // Dropping WhisperTranscriber drops WhisperContext and releases associated memory/VRAM.
-        *guard = None;
-    }
+/// Options of one run of the dictation pipeline
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PipelineOptions<'a> {
+    /// Translate to English instead of transcribing
+    pub translate: bool,
+    /// The text the speech continues, if known
+    pub prompt: Option<&'a str>,
+    /// Post-processing profile instead of the configured one
+    pub profile: Option<&'a str>,
 }
 
+/// The dictation pipeline after recording: transcribe (or translate) the captured audio
+/// and turn the result into the text to insert. Shared by hotkey dictation and `eval`.
+pub fn run_pipeline(
+    engine: &dyn Transcriber,
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+    language: &str,
+    options: &PipelineOptions,
+) -> Result<String, String> {
+    let PipelineOptions {
+        translate,
+        prompt,
+        profile,
+    } = *options;
+    let text = if translate {
+        translate_samples_with(engine, samples, sample_rate, channels, language)?
+    } else {
+        engine
+            .transcribe_with_prompt(samples, sample_rate, channels, Some(language), prompt)
+            .map_err(|e| format!("Failed to transcribe audio: {}", e))?
+    };
+    // Translations are English whatever was spoken
+    let output_language = if translate { "en" } else { language };
+    Ok(post_processing::process(&text, output_language, profile))
+}
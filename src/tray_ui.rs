@@ -5,6 +5,10 @@ use gtk::prelude::*;
 #[cfg(feature = "tray-icon")]
 use gtk::{AboutDialog, CheckMenuItem, Menu, MenuItem, SeparatorMenuItem, RadioMenuItem, Window, Label, WindowType, Box as GtkBox, Orientation, RadioButton, Entry};
 #[cfg(feature = "tray-icon")]
+use gtk::{ButtonsType, DialogFlags, FileChooserAction, FileChooserDialog, FileFilter, MessageDialog, MessageType, ResponseType};
+#[cfg(feature = "tray-icon")]
+use gtk::{Button, ComboBoxText, Grid, PolicyType, ScrolledWindow};
+#[cfg(feature = "tray-icon")]
 use gtk::gdk::{self, ModifierType};
 #[cfg(feature = "tray-icon")]
 use libappindicator::{AppIndicator, AppIndicatorStatus};
@@ -22,10 +26,16 @@ use std::sync::{mpsc::Sender, Mutex};
 #[cfg(feature = "tray-icon")]
 use lazy_static::lazy_static;
 
+#[cfg(feature = "tray-icon")]
+use crate::config::AppRule;
+#[cfg(feature = "tray-icon")]
+use crate::{local_models, model_manager, model_registry};
+
 #[cfg(feature = "tray-icon")]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TrayStatus {
     Priming,
+    Loading,
     Ready,
     Recording,
     Processing,
@@ -44,6 +54,10 @@ pub struct AppView {
     pub active_model: String,
     pub status: TrayStatus,
     pub loading: HashMap<String, ModelProgress>,
+    /// Last download error per model, until the next attempt
+    pub errors: HashMap<String, String>,
+    /// Model transcribing instead of the active one until its files are downloaded
+    pub fallback_model: Option<String>,
     pub translate_enabled: bool,
 }
 
@@ -52,6 +66,9 @@ pub struct AppView {
 #[derive(Debug, Clone)]
 pub enum UiIntent {
     SelectModel(String),
+    SelectDevice(String),
+    RedownloadModel(String),
+    CancelDownload(String),
     ToggleTranslate(bool),
     QuitRequested,
 }
@@ -66,10 +83,12 @@ lazy_static! {
 fn icon_name_for_status(status: TrayStatus, translate: bool) -> &'static str {
     match (status, translate) {
         (TrayStatus::Priming, false) => "voice-input-yellow",
+        (TrayStatus::Loading, false) => "voice-input-yellow",
         (TrayStatus::Ready, false) => "voice-input-white",
         (TrayStatus::Recording, false) => "voice-input-red",
         (TrayStatus::Processing, false) => "voice-input-blue",
         (TrayStatus::Priming, true) => "voice-input-translate-yellow",
+        (TrayStatus::Loading, true) => "voice-input-translate-yellow",
         (TrayStatus::Ready, true) => "voice-input-translate-white",
         (TrayStatus::Recording, true) => "voice-input-translate-red",
         (TrayStatus::Processing, true) => "voice-input-translate-blue",
@@ -178,6 +197,390 @@ fn format_shortcut_from_event(event: &gdk::EventKey) -> Option<String> {
     Some(out)
 }
 
+// Model selection item that asks the app to switch models when activated
+#[cfg(feature = "tray-icon")]
+fn model_check_item(name: &str, active_model: &str, intents_tx: &Sender<UiIntent>) -> CheckMenuItem {
+    let item = CheckMenuItem::with_label(name);
+    item.set_active(name == active_model);
+
+    let model_clone = name.to_string();
+    let intents_tx_clone = intents_tx.clone();
+    item.connect_activate(move |check_item| {
+        if check_item.is_active() {
+            let _ = intents_tx_clone.send(UiIntent::SelectModel(model_clone.clone()));
+        }
+    });
+    item
+}
+
+#[cfg(feature = "tray-icon")]
+fn show_error_dialog(text: &str) {
+    let msg = MessageDialog::new(
+        None::<&Window>,
+        DialogFlags::MODAL,
+        MessageType::Error,
+        ButtonsType::Ok,
+        text,
+    );
+    msg.run();
+    msg.close();
+}
+
+// Fill the "Manage models" grid: one row per registry model with its state and actions
+#[cfg(feature = "tray-icon")]
+fn fill_models_grid(grid: &Grid, intents_tx: &Sender<UiIntent>) {
+    for child in grid.children() {
+        grid.remove(&child);
+    }
+    for (col, title) in ["Model", "State", "Size", "Last used"].iter().enumerate() {
+        let label = Label::new(None);
+        label.set_markup(&format!("<b>{}</b>", title));
+        label.set_halign(gtk::Align::Start);
+        grid.attach(&label, col as i32, 0, 1, 1);
+    }
+
+    let mut total = 0;
+    for (i, status) in model_manager::list_models().into_iter().enumerate() {
+        let row = i as i32 + 1;
+        let id = status.info.id;
+        let in_use = model_manager::is_in_use(id);
+        total += status.size_on_disk;
+
+        let name_text = if in_use { format!("{} (active)", id) } else { id.to_string() };
+        let name = Label::new(Some(name_text.as_str()));
+        name.set_halign(gtk::Align::Start);
+        grid.attach(&name, 0, row, 1, 1);
+
+        let state = Label::new(Some(status.state.label()));
+        state.set_halign(gtk::Align::Start);
+        if let model_manager::ModelState::Corrupt(reason) = &status.state {
+            state.set_tooltip_text(Some(reason.as_str()));
+        }
+        grid.attach(&state, 1, row, 1, 1);
+
+        let size_text = if status.size_on_disk > 0 {
+            model_manager::format_size(status.size_on_disk)
+        } else {
+            "-".to_string()
+        };
+        let size = Label::new(Some(size_text.as_str()));
+        size.set_halign(gtk::Align::End);
+        grid.attach(&size, 2, row, 1, 1);
+
+        let last_used = Label::new(Some(model_manager::format_last_used(status.last_used).as_str()));
+        last_used.set_halign(gtk::Align::Start);
+        grid.attach(&last_used, 3, row, 1, 1);
+
+        let delete = Button::with_label("Delete");
+        delete.set_sensitive(status.state != model_manager::ModelState::Missing && !in_use);
+        if in_use {
+            delete.set_tooltip_text(Some("The active model cannot be deleted"));
+        }
+        {
+            let grid_clone = grid.clone();
+            let intents_tx_clone = intents_tx.clone();
+            delete.connect_clicked(move |_| {
+                if let Err(e) = model_manager::delete_model(id) {
+                    show_error_dialog(&format!("Cannot delete model:\n{}", e));
+                }
+                fill_models_grid(&grid_clone, &intents_tx_clone);
+            });
+        }
+        grid.attach(&delete, 4, row, 1, 1);
+
+        let download = Button::with_label(match status.state {
+            model_manager::ModelState::Missing | model_manager::ModelState::Partial => "Download",
+            _ => "Re-download",
+        });
+        {
+            let intents_tx_clone = intents_tx.clone();
+            download.connect_clicked(move |_| {
+                let _ = intents_tx_clone.send(UiIntent::RedownloadModel(id.to_string()));
+            });
+        }
+        grid.attach(&download, 5, row, 1, 1);
+    }
+
+    let total_label = Label::new(Some(format!("Total on disk: {}", model_manager::format_size(total)).as_str()));
+    total_label.set_halign(gtk::Align::Start);
+    grid.attach(&total_label, 0, model_registry::MODELS.len() as i32 + 1, 4, 1);
+    grid.show_all();
+}
+
+#[cfg(feature = "tray-icon")]
+fn show_manage_models_window(intents_tx: &Sender<UiIntent>) {
+    let win = Window::new(WindowType::Toplevel);
+    win.set_title("Manage models");
+    win.set_default_size(640, 480);
+
+    let vbox = GtkBox::new(Orientation::Vertical, 8);
+    vbox.set_margin_top(12);
+    vbox.set_margin_bottom(12);
+    vbox.set_margin_start(12);
+    vbox.set_margin_end(12);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(4);
+    grid.set_column_spacing(12);
+    fill_models_grid(&grid, intents_tx);
+
+    let scrolled = ScrolledWindow::new(None::<&gtk::Adjustment>, None::<&gtk::Adjustment>);
+    scrolled.set_policy(PolicyType::Never, PolicyType::Automatic);
+    scrolled.add(&grid);
+    vbox.pack_start(&scrolled, true, true, 0);
+
+    // Downloads run in the background; refresh shows their current state
+    let refresh = Button::with_label("Refresh");
+    {
+        let grid_clone = grid.clone();
+        let intents_tx_clone = intents_tx.clone();
+        refresh.connect_clicked(move |_| fill_models_grid(&grid_clone, &intents_tx_clone));
+    }
+    refresh.set_halign(gtk::Align::End);
+    vbox.pack_start(&refresh, false, false, 0);
+
+    win.add(&vbox);
+    win.show_all();
+    win.present();
+}
+
+// Widgets of one row in the "Application rules" window; empty fields use the defaults
+#[cfg(feature = "tray-icon")]
+struct RuleRow {
+    app: Entry,
+    method: ComboBoxText,
+    paste_keys: ComboBoxText,
+    clipboard_delay: Entry,
+    paste_delay: Entry,
+    restore_delay: Entry,
+    type_delay: Entry,
+    profile: ComboBoxText,
+    delete: Button,
+}
+
+#[cfg(feature = "tray-icon")]
+impl RuleRow {
+    fn new(app: &str, rule: &AppRule, profiles: &[String]) -> Self {
+        let app_entry = Entry::new();
+        app_entry.set_text(app);
+        app_entry.set_placeholder_text(Some("WM_CLASS or app name"));
+
+        let method = ComboBoxText::new();
+        for (id, label) in [("", "Default"), ("clipboard", "Clipboard"), ("type", "Type"), ("atspi", "AT-SPI")] {
+            method.append(Some(id), label);
+        }
+        method.set_active_id(Some(rule.method.as_deref().unwrap_or("")));
+
+        let paste_keys = ComboBoxText::with_entry();
+        for keys in ["ctrl+v", "ctrl+shift+v", "shift+insert"] {
+            paste_keys.append_text(keys);
+        }
+        set_combo_text(&paste_keys, rule.paste_keys.as_deref().unwrap_or(""));
+
+        let profile = ComboBoxText::with_entry();
+        for name in profiles {
+            profile.append_text(name);
+        }
+        set_combo_text(&profile, rule.profile.as_deref().unwrap_or(""));
+
+        Self {
+            app: app_entry,
+            method,
+            paste_keys,
+            clipboard_delay: delay_entry(rule.clipboard_delay_ms),
+            paste_delay: delay_entry(rule.paste_delay_ms),
+            restore_delay: delay_entry(rule.restore_delay_ms),
+            type_delay: delay_entry(rule.type_delay_ms),
+            profile,
+            delete: Button::with_label("Delete"),
+        }
+    }
+
+    // The rule as entered; Err names the field that is not a number
+    fn to_rule(&self) -> Result<(String, AppRule), String> {
+        let app = self.app.text().trim().to_string();
+        let delay = |entry: &Entry, field: &str| -> Result<Option<u64>, String> {
+            let text = entry.text();
+            let text = text.trim();
+            if text.is_empty() {
+                return Ok(None);
+            }
+            text.parse()
+                .map(Some)
+                .map_err(|_| format!("{}: {} must be a number of milliseconds", app, field))
+        };
+        let text = |combo: &ComboBoxText| {
+            combo
+                .active_text()
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty())
+        };
+        let rule = AppRule {
+            method: self.method.active_id().map(|id| id.to_string()).filter(|id| !id.is_empty()),
+            type_delay_ms: delay(&self.type_delay, "Type delay")?,
+            paste_keys: text(&self.paste_keys),
+            clipboard_delay_ms: delay(&self.clipboard_delay, "Clipboard delay")?,
+            paste_delay_ms: delay(&self.paste_delay, "Paste delay")?,
+            restore_delay_ms: delay(&self.restore_delay, "Restore delay")?,
+            profile: text(&self.profile),
+        };
+        Ok((app, rule))
+    }
+}
+
+#[cfg(feature = "tray-icon")]
+fn delay_entry(value: Option<u64>) -> Entry {
+    let entry = Entry::new();
+    entry.set_width_chars(6);
+    entry.set_placeholder_text(Some("default"));
+    if let Some(value) = value {
+        entry.set_text(&value.to_string());
+    }
+    entry
+}
+
+#[cfg(feature = "tray-icon")]
+fn set_combo_text(combo: &ComboBoxText, text: &str) {
+    if let Some(entry) = combo.child().and_then(|child| child.downcast::<Entry>().ok()) {
+        entry.set_text(text);
+    }
+}
+
+// Lay out the "Application rules" grid: a header and one row per rule
+#[cfg(feature = "tray-icon")]
+fn fill_rules_grid(grid: &Grid, rows: &Rc<RefCell<Vec<RuleRow>>>) {
+    for child in grid.children() {
+        grid.remove(&child);
+    }
+    let titles = [
+        "Application",
+        "Method",
+        "Paste keys",
+        "Clipboard delay",
+        "Paste delay",
+        "Restore delay",
+        "Type delay",
+        "Profile",
+    ];
+    for (col, title) in titles.iter().enumerate() {
+        let label = Label::new(None);
+        label.set_markup(&format!("<b>{}</b>", title));
+        label.set_halign(gtk::Align::Start);
+        grid.attach(&label, col as i32, 0, 1, 1);
+    }
+    for (i, row) in rows.borrow().iter().enumerate() {
+        let line = i as i32 + 1;
+        grid.attach(&row.app, 0, line, 1, 1);
+        grid.attach(&row.method, 1, line, 1, 1);
+        grid.attach(&row.paste_keys, 2, line, 1, 1);
+        grid.attach(&row.clipboard_delay, 3, line, 1, 1);
+        grid.attach(&row.paste_delay, 4, line, 1, 1);
+        grid.attach(&row.restore_delay, 5, line, 1, 1);
+        grid.attach(&row.type_delay, 6, line, 1, 1);
+        grid.attach(&row.profile, 7, line, 1, 1);
+        grid.attach(&row.delete, 8, line, 1, 1);
+    }
+    grid.show_all();
+}
+
+// Append a rule row whose Delete button removes it again
+#[cfg(feature = "tray-icon")]
+fn add_rule_row(grid: &Grid, rows: &Rc<RefCell<Vec<RuleRow>>>, app: &str, rule: &AppRule, profiles: &[String]) {
+    let row = RuleRow::new(app, rule, profiles);
+    {
+        let grid_clone = grid.clone();
+        let rows_clone = rows.clone();
+        let app_entry = row.app.clone();
+        row.delete.connect_clicked(move |_| {
+            rows_clone.borrow_mut().retain(|row| row.app != app_entry);
+            fill_rules_grid(&grid_clone, &rows_clone);
+        });
+    }
+    rows.borrow_mut().push(row);
+    fill_rules_grid(grid, rows);
+}
+
+#[cfg(feature = "tray-icon")]
+fn show_app_rules_window() {
+    let win = Window::new(WindowType::Toplevel);
+    win.set_title("Application rules");
+    win.set_default_size(960, 360);
+
+    let vbox = GtkBox::new(Orientation::Vertical, 8);
+    vbox.set_margin_top(12);
+    vbox.set_margin_bottom(12);
+    vbox.set_margin_start(12);
+    vbox.set_margin_end(12);
+
+    let help = Label::new(Some(
+        "Rules apply to the focused window by X11 WM_CLASS (see xprop WM_CLASS) or by the AT-SPI application name.\n\
+         Delays are in milliseconds; empty fields use the global insertion settings.",
+    ));
+    help.set_halign(gtk::Align::Start);
+    vbox.pack_start(&help, false, false, 0);
+
+    let mut profiles: Vec<String> = crate::config::get_post_processing().profiles.into_keys().collect();
+    profiles.sort();
+    let mut apps: Vec<(String, AppRule)> = crate::config::get_insertion().apps.into_iter().collect();
+    apps.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let grid = Grid::new();
+    grid.set_row_spacing(4);
+    grid.set_column_spacing(8);
+    let rows: Rc<RefCell<Vec<RuleRow>>> = Rc::new(RefCell::new(Vec::new()));
+    for (app, rule) in &apps {
+        add_rule_row(&grid, &rows, app, rule, &profiles);
+    }
+    fill_rules_grid(&grid, &rows);
+
+    let scrolled = ScrolledWindow::new(None::<&gtk::Adjustment>, None::<&gtk::Adjustment>);
+    scrolled.set_policy(PolicyType::Automatic, PolicyType::Automatic);
+    scrolled.add(&grid);
+    vbox.pack_start(&scrolled, true, true, 0);
+
+    let buttons = GtkBox::new(Orientation::Horizontal, 8);
+    let add = Button::with_label("Add rule");
+    {
+        let grid_clone = grid.clone();
+        let rows_clone = rows.clone();
+        add.connect_clicked(move |_| {
+            add_rule_row(&grid_clone, &rows_clone, "", &AppRule::default(), &profiles);
+        });
+    }
+    buttons.pack_start(&add, false, false, 0);
+
+    let save = Button::with_label("Save");
+    {
+        let rows_clone = rows.clone();
+        let win_clone = win.clone();
+        save.connect_clicked(move |_| {
+            let mut apps = HashMap::new();
+            for row in rows_clone.borrow().iter() {
+                match row.to_rule() {
+                    Ok((app, _)) if app.is_empty() => {}
+                    Ok((app, rule)) => {
+                        apps.insert(app, rule);
+                    }
+                    Err(e) => {
+                        show_error_dialog(&e);
+                        return;
+                    }
+                }
+            }
+            match crate::config::save_app_rules(apps) {
+                Ok(()) => win_clone.close(),
+                Err(e) => show_error_dialog(&format!("Cannot save application rules:\n{}", e)),
+            }
+        });
+    }
+    buttons.pack_end(&save, false, false, 0);
+    vbox.pack_start(&buttons, false, false, 0);
+
+    win.add(&vbox);
+    win.show_all();
+    win.present();
+}
+
 #[cfg(feature = "tray-icon")]
 pub fn init_tray_icon(
     intents_tx: Sender<UiIntent>,
@@ -238,32 +641,96 @@ pub fn init_tray_icon(
     // Model submenu
     let model_menu_item = MenuItem::with_label(&format!("Model: {}", initial_model));
     let model_menu = Menu::new();
-    let model_options = vec!["small", "medium", "large"];
-    let mut model_items: Vec<CheckMenuItem> = Vec::new();
+    // Model items by name (registry ids and custom model names), shared with the update handler
+    let model_items: Rc<RefCell<HashMap<String, CheckMenuItem>>> =
+        Rc::new(RefCell::new(HashMap::new()));
 
     // Channel for AppView snapshots
     let (tx, rx) = glib::MainContext::channel::<AppView>(Priority::DEFAULT);
     *TRAY_UI_TX.lock().unwrap() = Some(tx);
 
-    for model in &model_options {
-        let item = CheckMenuItem::with_label(model);
-        item.set_active(*model == initial_model);
+    // Quantized variants go into their own submenu to keep the list short
+    let quantized_menu_item = MenuItem::with_label("Quantized");
+    let quantized_menu = Menu::new();
+
+    for info in model_registry::selectable_models() {
+        let item = model_check_item(info.id, &initial_model, &intents_tx);
+        if info.is_quantized() {
+            quantized_menu.append(&item);
+        } else {
+            model_menu.append(&item);
+        }
+        model_items.borrow_mut().insert(info.id.to_string(), item);
+    }
+
+    quantized_menu_item.set_submenu(Some(&quantized_menu));
+    model_menu.append(&SeparatorMenuItem::new());
+    model_menu.append(&quantized_menu_item);
 
-        let model_clone = model.to_string();
+    // User-supplied model files (only those that validate are offered)
+    let local_menu_item = MenuItem::with_label("Local models");
+    let local_menu = Menu::new();
+    for local in local_models::available_custom_models() {
+        let item = model_check_item(&local.name, &initial_model, &intents_tx);
+        local_menu.append(&item);
+        model_items.borrow_mut().insert(local.name, item);
+    }
+    local_menu.append(&SeparatorMenuItem::new());
+    let add_local_item = MenuItem::with_label("Add local model file...");
+    {
+        let local_menu_clone = local_menu.clone();
+        let model_items_clone = model_items.clone();
         let intents_tx_clone = intents_tx.clone();
-        item.connect_activate(move |check_item| {
-            if check_item.is_active() {
-                let _ = intents_tx_clone.send(UiIntent::SelectModel(model_clone.clone()));
+        add_local_item.connect_activate(move |_| {
+            let dialog = FileChooserDialog::with_buttons(
+                Some("Select a ggml whisper model file"),
+                None::<&Window>,
+                FileChooserAction::Open,
+                &[("Cancel", ResponseType::Cancel), ("Add", ResponseType::Accept)],
+            );
+            let filter = FileFilter::new();
+            filter.set_name(Some("ggml models (*.bin)"));
+            filter.add_pattern("*.bin");
+            dialog.add_filter(filter);
+
+            let chosen = if dialog.run() == ResponseType::Accept {
+                dialog.filename()
+            } else {
+                None
+            };
+            dialog.close();
+
+            let Some(path) = chosen else {
+                return;
+            };
+            let name = local_models::default_model_name(&path);
+            match local_models::register_custom_model(&name, &path) {
+                Ok(local) => {
+                    println!("Registered local model '{}' at {}", local.name, local.path.display());
+                    let current = crate::config::get_selected_model();
+                    let item = model_check_item(&local.name, &current, &intents_tx_clone);
+                    local_menu_clone.prepend(&item);
+                    item.show();
+                    model_items_clone.borrow_mut().insert(local.name, item);
+                }
+                Err(e) => show_error_dialog(&format!("Cannot use this model file:\n{}", e)),
             }
         });
-
-        model_menu.append(&item);
-        model_items.push(item);
     }
+    local_menu.append(&add_local_item);
+    local_menu_item.set_submenu(Some(&local_menu));
+    model_menu.append(&local_menu_item);
 
     model_menu_item.set_submenu(Some(&model_menu));
     menu.append(&model_menu_item);
 
+    // Running downloads, one cancel entry per model; rebuilt on every view update
+    let downloads_menu_item = MenuItem::with_label("Downloads");
+    let downloads_menu = Menu::new();
+    downloads_menu_item.set_submenu(Some(&downloads_menu));
+    downloads_menu_item.set_no_show_all(true);
+    menu.append(&downloads_menu_item);
+
     // Separator
     menu.append(&SeparatorMenuItem::new());
 
@@ -271,6 +738,7 @@ pub fn init_tray_icon(
     let settings_item = MenuItem::with_label("Settings");
     {
         let settings_window_rc = settings_window.clone();
+        let intents_tx_settings = intents_tx.clone();
         settings_item.connect_activate(move |_| {
             // If already created, just present it
             if let Some(ref win) = *settings_window_rc.borrow() {
@@ -316,22 +784,26 @@ pub fn init_tray_icon(
                 vbox.pack_start(&note, false, false, 0);
             }
 
-            // Save handlers
+            // Save handlers: the app thread persists the device and reloads models in the background
             {
                 let rb_cpu_clone = rb_cpu.clone();
+                let intents_tx_clone = intents_tx_settings.clone();
                 rb_cpu.connect_toggled(move |btn| {
                     if btn.is_active() {
-                        let _ = crate::config::save_device("cpu");
+                        let _ = intents_tx_clone.send(UiIntent::SelectDevice("cpu".to_string()));
                         // Ensure mutual exclusivity visually
                         rb_cpu_clone.set_active(true);
                     }
                 });
             }
-            rb_gpu.connect_toggled(move |btn| {
-                if btn.is_active() {
-                    let _ = crate::config::save_device("gpu");
-                }
-            });
+            {
+                let intents_tx_clone = intents_tx_settings.clone();
+                rb_gpu.connect_toggled(move |btn| {
+                    if btn.is_active() {
+                        let _ = intents_tx_clone.send(UiIntent::SelectDevice("gpu".to_string()));
+                    }
+                });
+            }
 
             vbox.pack_start(&rb_cpu, false, false, 0);
             vbox.pack_start(&rb_gpu, false, false, 0);
@@ -419,6 +891,27 @@ pub fn init_tray_icon(
             vbox.pack_start(&record_label, false, false, 0);
             vbox.pack_start(&record_entry, false, false, 0);
 
+            // Downloaded models: state, disk usage, delete and re-download
+            let models_title = Label::new(Some("Models"));
+            models_title.set_halign(gtk::Align::Start);
+            vbox.pack_start(&models_title, false, false, 6);
+            let manage_models = Button::with_label("Manage models...");
+            {
+                let intents_tx_clone = intents_tx_settings.clone();
+                manage_models.connect_clicked(move |_| show_manage_models_window(&intents_tx_clone));
+            }
+            manage_models.set_halign(gtk::Align::Start);
+            vbox.pack_start(&manage_models, false, false, 0);
+
+            // Insertion method, paste keys, delays and profile per application
+            let insertion_title = Label::new(Some("Text insertion"));
+            insertion_title.set_halign(gtk::Align::Start);
+            vbox.pack_start(&insertion_title, false, false, 6);
+            let app_rules = Button::with_label("Application rules...");
+            app_rules.connect_clicked(|_| show_app_rules_window());
+            app_rules.set_halign(gtk::Align::Start);
+            vbox.pack_start(&app_rules, false, false, 0);
+
             win.add(&vbox);
 
             // Keep singleton reference; clear it on destroy
@@ -532,14 +1025,12 @@ pub fn init_tray_icon(
 
     // Apply AppView updates
     {
-        let mut items_map: HashMap<String, CheckMenuItem> = HashMap::new();
-        for (i, name) in model_options.iter().enumerate() {
-            if let Some(item) = model_items.get(i) {
-                items_map.insert((*name).to_string(), item.clone());
-            }
-        }
+        let items_map = model_items.clone();
         let indicator_for_rx = indicator.clone();
         let model_menu_item_for_rx = model_menu_item.clone();
+        let downloads_menu_item_for_rx = downloads_menu_item.clone();
+        let downloads_menu_for_rx = downloads_menu.clone();
+        let intents_tx_for_rx = intents_tx.clone();
         let translate_item_for_rx = translate_item.clone();
         let transcribe_item_for_rx = transcribe_item.clone();
 
@@ -552,7 +1043,7 @@ pub fn init_tray_icon(
             // Build top label and update items (show progress where available)
             let mut top_label = format!("Model: {}", view.active_model);
 
-            for (name, item) in items_map.iter() {
+            for (name, item) in items_map.borrow().iter() {
                 let is_active = *name == view.active_model;
                 item.set_active(is_active);
 
@@ -562,10 +1053,38 @@ pub fn init_tray_icon(
                     if is_active {
                         top_label = format!("Model: {} ({}% - {} left)", name, p.percent, eta);
                     }
+                } else if view.errors.contains_key(name) {
+                    item.set_label(&format!("{} (download failed)", name));
+                    if is_active {
+                        top_label = format!("Model: {} (download failed)", name);
+                    }
                 } else {
                     item.set_label(name);
                 }
+                item.set_tooltip_text(view.errors.get(name).map(|e| e.as_str()));
+            }
+
+            if let Some(fallback) = &view.fallback_model {
+                top_label = format!("{} - using {}", top_label, fallback);
+            }
+
+            // Offer cancellation for each running download
+            for child in downloads_menu_for_rx.children() {
+                downloads_menu_for_rx.remove(&child);
+            }
+            let mut downloading: Vec<_> = view.loading.iter().collect();
+            downloading.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, p) in downloading {
+                let cancel_item = MenuItem::with_label(&format!("Cancel {} ({}%)", name, p.percent));
+                let model = name.clone();
+                let intents_tx_clone = intents_tx_for_rx.clone();
+                cancel_item.connect_activate(move |_| {
+                    let _ = intents_tx_clone.send(UiIntent::CancelDownload(model.clone()));
+                });
+                downloads_menu_for_rx.append(&cancel_item);
+                cancel_item.show();
             }
+            downloads_menu_item_for_rx.set_visible(!view.loading.is_empty());
 
             // Reflect translate mode in radio items
             if view.translate_enabled {
@@ -597,6 +1116,7 @@ pub fn tray_post_view(view: AppView) {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TrayStatus {
     Priming,
+    Loading,
     Ready,
     Recording,
     Processing,
@@ -613,12 +1133,17 @@ pub struct AppView {
     pub active_model: String,
     pub status: TrayStatus,
     pub loading: std::collections::HashMap<String, ModelProgress>,
+    pub errors: std::collections::HashMap<String, String>,
+    pub fallback_model: Option<String>,
     pub translate_enabled: bool,
 }
 #[cfg(not(feature = "tray-icon"))]
 #[derive(Debug, Clone)]
 pub enum UiIntent {
     SelectModel(String),
+    SelectDevice(String),
+    RedownloadModel(String),
+    CancelDownload(String),
     ToggleTranslate(bool),
     QuitRequested,
 }
@@ -0,0 +1,51 @@
+use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+use std::thread;
+use std::time::Duration;
+
+use crate::insertion::InsertError;
+
+/// Types `text` at the cursor as synthesized key presses, leaving the clipboard alone.
+/// Any Unicode character can be typed whatever the keyboard layout.
+/// With a `delay`, characters are typed one at a time with that pause after each.
+/// A failure after the first key press is `InsertError::Partial`: typing again would repeat text.
+pub fn type_text(text: &str, delay: Duration) -> Result<(), InsertError> {
+    println!("Typing {} characters", text.chars().count());
+
+    let mut enigo = Enigo::new(&Settings::default())
+        .map_err(|e| InsertError::Failed(format!("Failed to start typing: {}", e)))?;
+
+    let mut typed = false;
+    let fail = |typed: bool, message: String| {
+        if typed {
+            InsertError::Partial(message)
+        } else {
+            InsertError::Failed(message)
+        }
+    };
+    for (n, line) in text.split('\n').enumerate() {
+        if n > 0 {
+            enigo
+                .key(Key::Return, Direction::Click)
+                .map_err(|e| fail(typed, format!("Failed to type a line break: {}", e)))?;
+            typed = true;
+            thread::sleep(delay);
+        }
+        if delay.is_zero() {
+            if !line.is_empty() {
+                enigo
+                    .text(line)
+                    .map_err(|e| fail(typed, format!("Failed to type text: {}", e)))?;
+                typed = true;
+            }
+            continue;
+        }
+        for c in line.chars() {
+            enigo
+                .text(c.encode_utf8(&mut [0; 4]))
+                .map_err(|e| fail(typed, format!("Failed to type '{}': {}", c, e)))?;
+            typed = true;
+            thread::sleep(delay);
+        }
+    }
+    Ok(())
+}
@@ -1,16 +1,72 @@
+use crate::audio_utils;
+use crate::compute_policy;
 use crate::config;
+use crate::http_client;
+use crate::model_integrity;
+use crate::model_registry::{self, ModelInfo};
+use crate::transcriber::Transcriber;
 use indicatif::{ProgressBar, ProgressStyle};
-use lazy_static::lazy_static;
 use reqwest::blocking::Client;
-use std::fs::File;
+use reqwest::header::RANGE;
+use reqwest::StatusCode;
+use std::fs::{self, OpenOptions};
 use std::io::{Read, Write};
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
-lazy_static! {
-    static ref DL_PROGRESS_CB: Mutex<Option<Box<dyn Fn(f64, u64) + Send + 'static>>> =
-        Mutex::new(None);
+/// Error returned by downloads stopped through their cancel flag
+pub const DOWNLOAD_CANCELLED: &str = "Download cancelled";
+
+/// Progress callback for downloads: (bytes downloaded, total bytes of the file)
+pub type DownloadProgressFn<'a> = &'a dyn Fn(u64, u64);
+
+/// Decoding settings that trade speed for accuracy
+#[derive(Debug, Clone)]
+pub struct DecodeOptions {
+    /// Beam search width; 1 means greedy decoding
+    pub beam_size: i32,
+    /// CPU threads; None leaves the whisper.cpp default
+    pub threads: Option<i32>,
+    /// Decode on a thread with lowered scheduling priority
+    pub low_priority: bool,
+    /// Text preceding the speech, passed to whisper as its initial prompt
+    pub initial_prompt: Option<String>,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            beam_size: 5,
+            // CPU builds use one thread per CPU; CUDA builds keep the library default
+            threads: if cfg!(feature = "cuda") {
+                None
+            } else {
+                Some(compute_policy::available_threads() as i32)
+            },
+            low_priority: false,
+            initial_prompt: None,
+        }
+    }
+}
+
+impl DecodeOptions {
+    /// Options for dictation: configured thread count (or the battery one) and priority
+    pub fn from_config() -> Self {
+        let compute = config::get_compute();
+        let configured = compute.threads > 0 || compute.battery_threads > 0;
+        let defaults = Self::default();
+        Self {
+            threads: if configured {
+                Some(compute_policy::decode_threads() as i32)
+            } else {
+                defaults.threads
+            },
+            low_priority: compute.low_priority,
+            ..defaults
+        }
+    }
 }
 
 pub struct WhisperTranscriber {
@@ -18,12 +74,6 @@ pub struct WhisperTranscriber {
 }
 
 impl WhisperTranscriber {
-    /// Set or clear a global download progress callback.
-    /// The callback receives (percent, eta_secs).
-    pub fn set_download_progress_callback(cb: Option<Box<dyn Fn(f64, u64) + Send + 'static>>) {
-        *DL_PROGRESS_CB.lock().unwrap() = cb;
-    }
-
     /// Initialize WhisperContext with CUDA support
     #[cfg(feature = "cuda")]
     fn init_with_cuda(model_path: &str) -> Result<WhisperContext, String> {
@@ -35,22 +85,55 @@ impl WhisperTranscriber {
         Ok(context)
     }
 
-    /// Create a new WhisperTranscriber with the specified model name
-    /// If the model doesn't exist, it will be downloaded automatically
-    pub fn new(model_name: &str) -> Result<Self, String> {
-        // Get the model path using the config module
-        let model_path_opt = config::get_model_path(model_name);
+    /// Create a new WhisperTranscriber with the specified model name on the requested device
+    /// (GPU is only honored in CUDA builds; CPU is used as a fallback).
+    /// If the model doesn't exist, it will be downloaded automatically; corrupt downloads
+    /// are detected and downloaded again.
+    pub fn new_on_device(model_name: &str, use_gpu: bool) -> Result<Self, String> {
+        let model_path = Self::locate_model(model_name)?;
+        match Self::load_context(&model_path, use_gpu) {
+            Ok(transcriber) => Ok(transcriber),
+            Err(e) if Self::is_redownloadable(model_name, &model_path) => {
+                eprintln!("{}. The model file looks corrupt; downloading it again", e);
+                model_integrity::remove_model_file(&model_path)?;
+                Self::download_model(model_name)?;
+                let model_path = Self::locate_model(model_name)?;
+                Self::load_context(&model_path, use_gpu)
+            }
+            Err(e) => Err(e),
+        }
+    }
 
-        // If model doesn't exist in either location, download it
-        if model_path_opt.is_none() {
-            println!("Model file not found. Downloading...");
-            Self::download_model(model_name)?;
+    /// Find a usable model file, downloading it when missing or corrupt
+    fn locate_model(model_name: &str) -> Result<PathBuf, String> {
+        // Get the model path using the config module
+        match config::get_model_path(model_name) {
+            Some(path) => match model_integrity::verify_model_file(&path) {
+                Ok(()) => return Ok(path),
+                Err(e) if Self::is_redownloadable(model_name, &path) => {
+                    eprintln!("Model file is corrupt: {}. Downloading it again...", e);
+                    model_integrity::remove_model_file(&path)?;
+                }
+                Err(e) => return Err(e),
+            },
+            None => println!("Model file not found. Downloading..."),
         }
 
-        // Get the path again after potential download
-        let model_path = config::get_model_path(model_name)
-            .ok_or_else(|| format!("Failed to locate model file after download: {}", model_name))?;
+        Self::download_model(model_name)?;
+
+        // Get the path again after the download
+        config::get_model_path(model_name)
+            .ok_or_else(|| format!("Failed to locate model file after download: {}", model_name))
+    }
+
+    /// Only registry models in the user models directory are replaced automatically;
+    /// user-supplied files and read-only shared directories are left alone.
+    fn is_redownloadable(model_name: &str, path: &Path) -> bool {
+        model_registry::find_by_file(model_name).is_some()
+            && config::get_models_dir().is_some_and(|dir| path.starts_with(dir))
+    }
 
+    fn load_context(model_path: &Path, use_gpu: bool) -> Result<Self, String> {
         // Convert PathBuf to string for the whisper-rs functions
         let model_path_str = model_path
             .to_str()
@@ -59,9 +142,9 @@ impl WhisperTranscriber {
         println!("Loading whisper model: {}", model_path_str);
         let start_time = std::time::Instant::now();
 
-        // Create context with CUDA support when available and selected in config
+        // Create context with CUDA support when available and requested
         #[cfg(feature = "cuda")]
-        if config::use_gpu() {
+        if use_gpu {
             match Self::init_with_cuda(model_path_str) {
                 Ok(context) => {
                     let load_duration = start_time.elapsed();
@@ -77,6 +160,9 @@ impl WhisperTranscriber {
             }
         }
 
+        #[cfg(not(feature = "cuda"))]
+        let _ = use_gpu;
+
         // CPU fallback or default path when CUDA is not enabled
         let mut temp_params = WhisperContextParameters::default();
         // If compiled with CUDA but user selected CPU, make sure GPU is disabled
@@ -93,37 +179,118 @@ impl WhisperTranscriber {
         Ok(WhisperTranscriber { context })
     }
 
-    /// Download the Whisper model from the official repository
+    /// Run a short inference over one second of silence so the first real
+    /// transcription doesn't pay one-time initialization costs.
+    pub fn warm_up(&self) {
+        let silence = vec![0.0f32; 16000];
+        let start_time = Instant::now();
+        match self.transcribe_samples(&silence, 16000, 1, Some("en")) {
+            Ok(_) => println!("Model warm-up finished in {:.2?}", start_time.elapsed()),
+            Err(e) => eprintln!("Model warm-up failed: {}", e),
+        }
+    }
+
+    /// Download the Whisper model from the configured mirrors (upstream whisper.cpp repository by default)
     pub fn download_model(model_name: &str) -> Result<(), String> {
-        // Base URL for Whisper models
-        let base_url = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/";
-        let url = format!("{}{}", base_url, model_name);
+        Self::download_model_with(model_name, &|_, _| {}, &AtomicBool::new(false))
+    }
 
-        println!("Downloading model from: {}", url);
+    /// Download a model file reporting progress, stopping early when `cancel` is set.
+    /// A cancelled download keeps its `.part` file so it can be resumed later.
+    pub fn download_model_with(
+        model_name: &str,
+        on_progress: DownloadProgressFn,
+        cancel: &AtomicBool,
+    ) -> Result<(), String> {
+        // Only files listed in the model registry can be downloaded
+        let info = model_registry::find_by_file(model_name).ok_or_else(|| {
+            format!(
+                "Model file {} is not in the model registry and cannot be downloaded",
+                model_name
+            )
+        })?;
+        Self::download_from_mirrors(info, on_progress, cancel)
+    }
+
+    /// Try each configured mirror in turn until one delivers a verified file
+    fn download_from_mirrors(
+        info: &ModelInfo,
+        on_progress: DownloadProgressFn,
+        cancel: &AtomicBool,
+    ) -> Result<(), String> {
+        if config::is_offline() {
+            return Err(Self::offline_missing_model_message(info.file_name));
+        }
+
+        // Create a client with increased timeout (and the configured proxy)
+        let client = http_client::blocking_client(Duration::from_secs(300))?; // 5 minute timeout
+
+        let mirrors = config::get_model_mirrors();
+        let mut errors = Vec::new();
+        for base_url in &mirrors {
+            let url = format!("{}{}", base_url, info.file_name);
+            println!(
+                "Downloading model {} (~{} MB) from: {}",
+                info.id, info.size_mb, url
+            );
+            match Self::download_from(&client, &url, info, on_progress, cancel) {
+                Ok(()) => return Ok(()),
+                Err(e) if cancel.load(Ordering::SeqCst) => return Err(e),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    errors.push(format!("{}: {}", base_url, e));
+                }
+            }
+        }
 
-        // Create a client with increased timeout
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(300)) // 5 minute timeout
-            .build()
-            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        Err(format!(
+            "Failed to download model {} from {} mirror(s): {}",
+            info.file_name,
+            mirrors.len(),
+            errors.join("; ")
+        ))
+    }
 
-        // Maximum number of retries
+    /// Explain where to put a model file by hand when downloads are disabled
+    fn offline_missing_model_message(model_name: &str) -> String {
+        let dirs: Vec<String> = config::get_model_search_dirs()
+            .iter()
+            .map(|d| d.display().to_string())
+            .collect();
+        let mirror = config::get_model_mirrors()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| config::DEFAULT_MODEL_MIRROR.to_string());
+        format!(
+            "Offline mode: model file {} is missing. Copy it (e.g. from {}{}) into one of: {}",
+            model_name,
+            mirror,
+            model_name,
+            dirs.join(", ")
+        )
+    }
+
+    /// Download from a single mirror, retrying with backoff
+    fn download_from(
+        client: &Client,
+        url: &str,
+        info: &ModelInfo,
+        on_progress: DownloadProgressFn,
+        cancel: &AtomicBool,
+    ) -> Result<(), String> {
+        // Maximum number of retries; each retry resumes the partial download
         let max_retries = 3;
         let mut retry_count = 0;
         let mut last_error = String::new();
 
         // Retry loop
         while retry_count < max_retries {
-            match Self::download_with_retry(&client, &url, model_name, retry_count) {
-                Ok(_) => {
-                    // Get the path where the model was saved for display purposes
-                    if let Ok(path) = config::get_model_save_path(model_name) {
-                        println!("Model downloaded successfully to: {}", path.display());
-                    } else {
-                        println!("Model downloaded successfully");
-                    }
+            match Self::download_with_retry(client, url, info, retry_count, on_progress, cancel) {
+                Ok(path) => {
+                    println!("Model downloaded successfully to: {}", path.display());
                     return Ok(());
                 }
+                Err(e) if cancel.load(Ordering::SeqCst) => return Err(e),
                 Err(e) => {
                     last_error = e;
                     retry_count += 1;
@@ -131,8 +298,9 @@ impl WhisperTranscriber {
                         let wait_time =
                             std::time::Duration::from_secs(2u64.pow(retry_count as u32));
                         println!(
-                            "Download attempt {} failed. Retrying in {} seconds...",
+                            "Download attempt {} failed: {}. Retrying in {} seconds...",
                             retry_count,
+                            last_error,
                             wait_time.as_secs()
                         );
                         std::thread::sleep(wait_time);
@@ -142,104 +310,213 @@ impl WhisperTranscriber {
         }
 
         Err(format!(
-            "Failed to download model {} after {} attempts: {}",
-            model_name, max_retries, last_error
+            "Failed to download {} after {} attempts: {}",
+            url, max_retries, last_error
         ))
     }
 
-    /// Helper function to download with retry logic
+    /// Helper function to download with retry logic.
+    /// Data goes to a `.part` file that is resumed via HTTP Range, verified against
+    /// the expected SHA-256 and atomically renamed into place.
     fn download_with_retry(
         client: &Client,
         url: &str,
-        model_name: &str,
+        info: &ModelInfo,
         attempt: usize,
-    ) -> Result<(), String> {
+        on_progress: DownloadProgressFn,
+        cancel: &AtomicBool,
+    ) -> Result<PathBuf, String> {
+        // Get the path where the model should be saved (in XDG data directory)
+        let model_path = config::get_model_save_path(info.file_name)
+            .map_err(|e| format!("Failed to determine model save path: {}", e))?;
+        let part_path = model_integrity::part_path(&model_path);
+
+        // Resume from what an earlier attempt left behind
+        let mut existing = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+        let mut request = client.get(url);
+        if existing > 0 {
+            println!("Resuming download at {} bytes", existing);
+            request = request.header(RANGE, format!("bytes={}-", existing));
+        }
+
         // Make a request to get the file
-        let mut response = client
-            .get(url)
+        let mut response = request
             .send()
             .map_err(|e| format!("Failed to download model (attempt {}): {}", attempt + 1, e))?;
 
-        // Check if the request was successful
-        if !response.status().is_success() {
+        let status = response.status();
+        if status == StatusCode::RANGE_NOT_SATISFIABLE && existing > 0 {
+            // The partial file already holds everything; verify it below
+            println!("Partial download is already complete");
+        } else if !status.is_success() {
+            // Check if the request was successful
             return Err(format!(
                 "Failed to download model (attempt {}): HTTP status {}",
                 attempt + 1,
-                response.status()
+                status
             ));
+        } else if status != StatusCode::PARTIAL_CONTENT && existing > 0 {
+            // Server ignored the range request; start over
+            println!("Server does not support resuming; restarting download");
+            existing = 0;
         }
 
-        // Get the content length for progress reporting
-        let total_size = response.content_length().unwrap_or(0);
+        // Checksum advertised with the file itself; a 416 response carries none
+        let advertised_sha = if status == StatusCode::RANGE_NOT_SATISFIABLE {
+            None
+        } else {
+            ["x-linked-etag", "etag"].iter().find_map(|h| {
+                response
+                    .headers()
+                    .get(*h)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(model_integrity::sha256_from_etag)
+            })
+        };
 
-        // Create a progress bar
-        let pb = ProgressBar::new(total_size);
-        pb.set_style(ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
-            .unwrap()
-            .progress_chars("#>-"));
+        if status != StatusCode::RANGE_NOT_SATISFIABLE {
+            // Get the content length for progress reporting
+            let remaining = response.content_length().unwrap_or(0);
+            let total_size = existing + remaining;
+
+            // Make sure the rest of the file fits on disk before writing anything
+            let models_dir = config::ensure_models_dir()
+                .map_err(|e| format!("Failed to create models directory: {}", e))?;
+            if let Ok(available) = fs2::available_space(&models_dir) {
+                let needed = if remaining > 0 {
+                    remaining
+                } else {
+                    (info.size_mb * 1024 * 1024).saturating_sub(existing)
+                };
+                if available < needed {
+                    return Err(format!(
+                        "Not enough disk space in {}: {} MB needed, {} MB available",
+                        models_dir.display(),
+                        needed / (1024 * 1024),
+                        available / (1024 * 1024)
+                    ));
+                }
+            }
 
-        // Get the path where the model should be saved (in XDG data directory)
-        let model_path = config::get_model_save_path(model_name)
-            .map_err(|e| format!("Failed to determine model save path: {}", e))?;
+            // Create a progress bar
+            let pb = ProgressBar::new(total_size);
+            pb.set_style(ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+                .unwrap()
+                .progress_chars("#>-"));
+            pb.set_position(existing);
+
+            println!("Saving model to: {}", part_path.display());
+
+            // Append to the partial file, or create it from scratch
+            let mut file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(existing > 0)
+                .truncate(existing == 0)
+                .open(&part_path)
+                .map_err(|e| format!("Failed to create model file: {}", e))?;
+
+            // Use a buffer to read the response in chunks
+            let mut buffer = [0; 8192]; // 8KB buffer
+            let mut downloaded: u64 = existing;
+
+            // Throttle progress reports
+            let mut last_emit = Instant::now();
+            let emit_every = Duration::from_millis(200);
+            on_progress(downloaded, total_size);
+
+            // Read and write in chunks
+            loop {
+                if cancel.load(Ordering::SeqCst) {
+                    pb.abandon();
+                    return Err(DOWNLOAD_CANCELLED.to_string());
+                }
 
-        println!("Saving model to: {}", model_path.display());
+                let bytes_read = match response.read(&mut buffer) {
+                    Ok(0) => break, // End of file
+                    Ok(n) => n,
+                    Err(e) => return Err(format!("Failed to read from response: {}", e)),
+                };
 
-        // Create the file
-        let mut file =
-            File::create(&model_path).map_err(|e| format!("Failed to create model file: {}", e))?;
+                file.write_all(&buffer[..bytes_read])
+                    .map_err(|e| format!("Failed to write to file: {}", e))?;
 
-        // Use a buffer to read the response in chunks
-        let mut buffer = [0; 8192]; // 8KB buffer
-        let mut downloaded: u64 = 0;
+                downloaded += bytes_read as u64;
+                pb.set_position(downloaded);
 
-        // Timing for ETA and throttling
-        let start_time = Instant::now();
-        let mut last_emit = start_time;
-        let emit_every = Duration::from_millis(200);
-
-        // Read and write in chunks
-        loop {
-            let bytes_read = match response.read(&mut buffer) {
-                Ok(0) => break, // End of file
-                Ok(n) => n,
-                Err(e) => return Err(format!("Failed to read from response: {}", e)),
-            };
-
-            file.write_all(&buffer[..bytes_read])
-                .map_err(|e| format!("Failed to write to file: {}", e))?;
-
-            downloaded += bytes_read as u64;
-            pb.set_position(downloaded);
-
-            // Emit progress to callback if available and total_size is known
-            if total_size > 0 {
-                let now = Instant::now();
-                if now.duration_since(last_emit) >= emit_every || downloaded == total_size {
-                    let elapsed = now.duration_since(start_time).as_secs_f64();
-                    let rate = if elapsed > 0.0 {
-                        downloaded as f64 / elapsed
-                    } else {
-                        0.0
-                    };
-                    let remaining_bytes = (total_size.saturating_sub(downloaded)) as f64;
-                    let eta_secs = if rate > 0.0 {
-                        (remaining_bytes / rate).round() as u64
-                    } else {
-                        0
-                    };
-                    let percent = (downloaded as f64 / total_size as f64) * 100.0;
-
-                    if let Some(ref cb) = *DL_PROGRESS_CB.lock().unwrap() {
-                        cb(percent, eta_secs);
+                // Report progress when total_size is known
+                if total_size > 0 {
+                    let now = Instant::now();
+                    if now.duration_since(last_emit) >= emit_every || downloaded == total_size {
+                        on_progress(downloaded, total_size);
+                        last_emit = now;
                     }
-                    last_emit = now;
                 }
             }
+
+            file.sync_all()
+                .map_err(|e| format!("Failed to flush model file: {}", e))?;
+            pb.finish_with_message("Download complete");
+
+            if total_size > 0 && downloaded != total_size {
+                return Err(format!(
+                    "Download interrupted at {} of {} bytes",
+                    downloaded, total_size
+                ));
+            }
+        }
+
+        // Verify before the file becomes visible under its final name
+        let expected_sha = Self::expected_sha256(info, url, advertised_sha);
+        let actual_sha = model_integrity::sha256_file(&part_path)?;
+        match expected_sha {
+            Some(ref expected) if *expected != actual_sha => {
+                // A corrupt partial file can't be resumed; drop it so the next attempt starts over
+                let _ = fs::remove_file(&part_path);
+                return Err(format!(
+                    "Checksum mismatch for {}: expected {}, got {}",
+                    info.file_name, expected, actual_sha
+                ));
+            }
+            Some(_) => println!("Checksum verified for {}", info.file_name),
+            None => eprintln!(
+                "Warning: no checksum is known for {}; recording {} unverified",
+                info.file_name, actual_sha
+            ),
         }
 
-        pb.finish_with_message("Download complete");
-        Ok(())
+        let size = fs::metadata(&part_path)
+            .map_err(|e| format!("Failed to stat downloaded file: {}", e))?
+            .len();
+        model_integrity::write_checksum(&model_path, &actual_sha, size)?;
+        fs::rename(&part_path, &model_path)
+            .map_err(|e| format!("Failed to move downloaded model into place: {}", e))?;
+
+        Ok(model_path)
+    }
+
+    /// SHA-256 a download must match: pinned in the registry (a mismatch always fails),
+    /// otherwise published by the upstream repository, otherwise the one the serving mirror sends
+    fn expected_sha256(info: &ModelInfo, url: &str, advertised: Option<String>) -> Option<String> {
+        if let Some(sha) = info.sha256 {
+            return Some(sha.to_string());
+        }
+        let upstream = format!("{}{}", config::DEFAULT_MODEL_MIRROR, info.file_name);
+        let published = if upstream == url {
+            None
+        } else {
+            println!("Looking up the checksum of {} upstream", info.file_name);
+            http_client::published_sha256(&upstream)
+        };
+        published.or_else(|| {
+            let sha = advertised.or_else(|| http_client::published_sha256(url))?;
+            eprintln!(
+                "Warning: {} has no pinned checksum; verifying it against the checksum sent by {}",
+                info.file_name, url
+            );
+            Some(sha)
+        })
     }
 
     /// Transcribe audio directly from in-memory samples.
@@ -250,6 +527,18 @@ impl WhisperTranscriber {
         sample_rate: u32,
         channels: u16,
         language: Option<&str>,
+    ) -> Result<String, String> {
+        self.transcribe_with_prompt(samples, sample_rate, channels, language, None)
+    }
+
+    /// `transcribe_samples` with the text that precedes the speech as whisper's initial prompt
+    pub fn transcribe_with_prompt(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        language: Option<&str>,
+        prompt: Option<&str>,
     ) -> Result<String, String> {
         println!(
             "Transcribing {} samples at {} Hz, {} channels",
@@ -258,73 +547,13 @@ impl WhisperTranscriber {
             channels
         );
 
-        // Convert to mono if needed
-        let mono_samples = if channels > 1 {
-            self.convert_to_mono(samples, channels as usize)
-        } else {
-            samples.to_vec()
+        // Convert to 16kHz mono if needed
+        let audio_data = audio_utils::to_whisper_input(samples, sample_rate, channels);
+        let options = DecodeOptions {
+            initial_prompt: prompt.map(str::to_string),
+            ..DecodeOptions::from_config()
         };
-
-        // Resample to 16kHz if needed
-        let target_sample_rate = 16000;
-        let audio_data = if sample_rate != target_sample_rate {
-            self.resample(&mono_samples, sample_rate, target_sample_rate)?
-        } else {
-            mono_samples
-        };
-
-        // Create parameters for transcription
-        let mut params = FullParams::new(SamplingStrategy::BeamSearch {
-            beam_size: 5,
-            patience: 1.2,
-        });
-
-        // Set parameters as needed
-        params.set_print_special(false);
-        params.set_print_progress(false);
-        params.set_print_realtime(false);
-        params.set_print_timestamps(true);
-        params.set_temperature(0.0);
-
-        // Set number of threads to use (CPU only)
-        #[cfg(not(feature = "cuda"))]
-        {
-            params.set_n_threads(8);
-        }
-
-        // Set language if provided (use 2-letter code if possible)
-        if let Some(lang) = language {
-            let lang_code = if lang.len() >= 2 { &lang[0..2] } else { lang };
-            params.set_language(Some(lang_code));
-        }
-
-        // Create a state for the context
-        let mut state = self
-            .context
-            .create_state()
-            .map_err(|e| format!("Failed to create state: {}", e))?;
-
-        // Process the audio
-        state
-            .full(params, &audio_data[..])
-            .map_err(|e| format!("Failed to process audio: {}", e))?;
-
-        // Extract the transcript
-        let num_segments = state
-            .full_n_segments()
-            .map_err(|e| format!("Failed to get number of segments: {}", e))?;
-
-        let mut transcript = String::new();
-        for i in 0..num_segments {
-            let segment = state
-                .full_get_segment_text(i)
-                .map_err(|e| format!("Failed to get segment {}: {}", i, e))?;
-            let short_segment = &segment.strip_prefix(" ");
-            transcript.push_str(short_segment.unwrap_or(&segment));
-            transcript.push('\n');
-        }
-
-        Ok(transcript)
+        self.decode(&audio_data, language, false, &options)
     }
 
     /// Translate audio (to English) directly from in-memory samples.
@@ -343,29 +572,55 @@ impl WhisperTranscriber {
             channels
         );
 
-        // Convert to mono if needed
-        let mono_samples = if channels > 1 {
-            self.convert_to_mono(samples, channels as usize)
-        } else {
-            samples.to_vec()
-        };
+        // Convert to 16kHz mono if needed
+        let audio_data = audio_utils::to_whisper_input(samples, sample_rate, channels);
+        // For translation we let whisper auto-detect input language by not setting language.
+        self.decode(&audio_data, None, true, &DecodeOptions::from_config())
+    }
+
+    /// Run whisper over 16kHz mono audio and return the text, one segment per line
+    pub fn decode(
+        &self,
+        audio_data: &[f32],
+        language: Option<&str>,
+        translate: bool,
+        options: &DecodeOptions,
+    ) -> Result<String, String> {
+        if !options.low_priority {
+            return self.decode_on_current_thread(audio_data, language, translate, options);
+        }
+        // Niceness can't be raised back without privileges, so lower it on a throwaway thread;
+        // whisper.cpp's worker threads inherit it
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    compute_policy::lower_thread_priority();
+                    self.decode_on_current_thread(audio_data, language, translate, options)
+                })
+                .join()
+                .unwrap_or_else(|_| Err("Decoding thread panicked".to_string()))
+        })
+    }
 
-        // Resample to 16kHz if needed
-        let target_sample_rate = 16000;
-        let audio_data = if sample_rate != target_sample_rate {
-            self.resample(&mono_samples, sample_rate, target_sample_rate)?
+    fn decode_on_current_thread(
+        &self,
+        audio_data: &[f32],
+        language: Option<&str>,
+        translate: bool,
+        options: &DecodeOptions,
+    ) -> Result<String, String> {
+        // Beam search is more accurate; a beam of 1 falls back to faster greedy decoding
+        let mut params = if options.beam_size > 1 {
+            FullParams::new(SamplingStrategy::BeamSearch {
+                beam_size: options.beam_size,
+                patience: 1.2,
+            })
         } else {
-            mono_samples
+            FullParams::new(SamplingStrategy::Greedy { best_of: 1 })
         };
 
-        // Create parameters for translation
-        let mut params = FullParams::new(SamplingStrategy::BeamSearch {
-            beam_size: 5,
-            patience: 1.2,
-        });
-
         // Enable translation mode
-        params.set_translate(true);
+        params.set_translate(translate);
 
         // Keep output clean
         params.set_print_special(false);
@@ -374,13 +629,19 @@ impl WhisperTranscriber {
         params.set_print_timestamps(true);
         params.set_temperature(0.0);
 
-        // Set number of threads to use (CPU only)
-        #[cfg(not(feature = "cuda"))]
-        {
-            params.set_n_threads(8);
+        if let Some(threads) = options.threads {
+            params.set_n_threads(threads);
         }
 
-        // For translation we let whisper auto-detect input language by not setting language.
+        if let Some(prompt) = options.initial_prompt.as_deref() {
+            params.set_initial_prompt(prompt);
+        }
+
+        // Set language if provided (use 2-letter code if possible)
+        if let Some(lang) = language {
+            let lang_code = if lang.len() >= 2 { &lang[0..2] } else { lang };
+            params.set_language(Some(lang_code));
+        }
 
         // Create a state for the context
         let mut state = self
@@ -390,10 +651,10 @@ impl WhisperTranscriber {
 
         // Process the audio
         state
-            .full(params, &audio_data[..])
+            .full(params, audio_data)
             .map_err(|e| format!("Failed to process audio: {}", e))?;
 
-        // Extract the translated text
+        // Extract the transcript
         let num_segments = state
             .full_n_segments()
             .map_err(|e| format!("Failed to get number of segments: {}", e))?;
@@ -411,41 +672,6 @@ impl WhisperTranscriber {
         Ok(transcript)
     }
 
-    /// Convert multi-channel audio to mono by averaging channels
-    fn convert_to_mono(&self, samples: &[f32], channels: usize) -> Vec<f32> {
-        let mono_len = samples.len() / channels;
-        let mut mono_samples = Vec::with_capacity(mono_len);
-
-        for i in 0..mono_len {
-            let mut sum = 0.0;
-            for c in 0..channels {
-                sum += samples[i * channels + c];
-            }
-            mono_samples.push(sum / channels as f32);
-        }
-
-        mono_samples
-    }
-
-    /// Simple linear resampling
-    fn resample(&self, samples: &[f32], from_rate: u32, to_rate: u32) -> Result<Vec<f32>, String> {
-        let ratio = from_rate as f64 / to_rate as f64;
-        let new_len = (samples.len() as f64 / ratio) as usize;
-        let mut resampled = Vec::with_capacity(new_len);
-
-        for i in 0..new_len {
-            let pos = i as f64 * ratio;
-            let pos_floor = pos.floor() as usize;
-            let pos_ceil = (pos_floor + 1).min(samples.len() - 1);
-            let frac = pos - pos_floor as f64;
-
-            let sample = samples[pos_floor] * (1.0 - frac as f32) + samples[pos_ceil] * frac as f32;
-            resampled.push(sample);
-        }
-
-        Ok(resampled)
-    }
-
     /// For CUDA builds: force a device reset to release GPU memory held by CUDA contexts/caches.
     #[cfg(feature = "cuda")]
     pub fn free_cuda_vram() {
@@ -464,3 +690,212 @@ impl WhisperTranscriber {
         }
     }
 }
+
+impl Transcriber for WhisperTranscriber {
+    fn transcribe_samples(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        language: Option<&str>,
+    ) -> Result<String, String> {
+        WhisperTranscriber::transcribe_samples(self, samples, sample_rate, channels, language)
+    }
+
+    fn transcribe_with_prompt(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        language: Option<&str>,
+        prompt: Option<&str>,
+    ) -> Result<String, String> {
+        WhisperTranscriber::transcribe_with_prompt(
+            self,
+            samples,
+            sample_rate,
+            channels,
+            language,
+            prompt,
+        )
+    }
+
+    fn translate_samples(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+        language: Option<&str>,
+    ) -> Result<String, String> {
+        WhisperTranscriber::translate_samples(self, samples, sample_rate, channels, language)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+    use std::sync::{Arc, Mutex};
+    use tiny_http::{Header, Response, Server};
+
+    // Registry-style entry without a pinned checksum, so the stand-in payload verifies
+    const TEST_MODEL: ModelInfo = ModelInfo {
+        id: "download-test",
+        file_name: "ggml-download-test.bin",
+        english_only: false,
+        size_mb: 1,
+        sha256: None,
+        multilingual: None,
+    };
+
+    /// Requests seen by the stand-in mirror: (URL, Range header)
+    type Seen = Arc<Mutex<Vec<(String, Option<String>)>>>;
+
+    /// Local stand-in for a model mirror: serves `payload` under `/good/` with its SHA-256
+    /// as ETag and honors `Range: bytes=N-`; every other path is a 404
+    fn serve(payload: Vec<u8>) -> (String, Seen) {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let base = format!("http://{}/", server.server_addr().to_ip().unwrap());
+        let seen: Seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        let etag = format!("\"{:x}\"", Sha256::digest(&payload));
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let range = request
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.equiv("Range"))
+                    .map(|h| h.value.to_string());
+                log.lock()
+                    .unwrap()
+                    .push((request.url().to_string(), range.clone()));
+                if !request
+                    .url()
+                    .ends_with(&format!("/good/{}", TEST_MODEL.file_name))
+                {
+                    let _ = request.respond(Response::empty(404));
+                    continue;
+                }
+                let start = range
+                    .as_deref()
+                    .and_then(|r| r.strip_prefix("bytes="))
+                    .and_then(|r| r.trim_end_matches('-').parse::<usize>().ok())
+                    .unwrap_or(0);
+                let (status, body) = if start >= payload.len() {
+                    (416, Vec::new())
+                } else if start > 0 {
+                    (206, payload[start..].to_vec())
+                } else {
+                    (200, payload.clone())
+                };
+                let header = Header::from_bytes(&b"ETag"[..], etag.as_bytes()).unwrap();
+                let _ = request.respond(
+                    Response::from_data(body)
+                        .with_status_code(status)
+                        .with_header(header),
+                );
+            }
+        });
+        (base, seen)
+    }
+
+    fn write_config(path: &Path, settings: serde_json::Value) {
+        let mut config = serde_json::json!({ "selected_model": "tiny" });
+        config
+            .as_object_mut()
+            .unwrap()
+            .extend(settings.as_object().unwrap().clone());
+        fs::write(path, config.to_string()).unwrap();
+    }
+
+    fn download() -> Result<(), String> {
+        WhisperTranscriber::download_from_mirrors(&TEST_MODEL, &|_, _| {}, &AtomicBool::new(false))
+    }
+
+    #[test]
+    fn download_fails_over_resumes_and_respects_offline_mode() {
+        let _lock = config::TEST_CONFIG_LOCK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let dir = std::env::temp_dir().join(format!("voice_input_download_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        std::env::set_var("XDG_DATA_HOME", &dir);
+        let config_file = dir.join("config.json");
+        let _config = config::use_config_file(Some(config_file.clone()));
+
+        let payload: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let (base, seen) = serve(payload.clone());
+        let model_path = config::get_model_save_path(TEST_MODEL.file_name).unwrap();
+        let part = model_integrity::part_path(&model_path);
+        let start_over = |partial: &[u8]| {
+            let _ = model_integrity::remove_model_file(&model_path);
+            if !partial.is_empty() {
+                fs::write(&part, partial).unwrap();
+            }
+            seen.lock().unwrap().clear();
+        };
+        let good = format!("{}good/", base);
+
+        // The first mirror doesn't have the file; the second one does
+        write_config(
+            &config_file,
+            serde_json::json!({ "model_mirrors": [format!("{}missing/", base), good] }),
+        );
+        download().unwrap();
+        assert_eq!(fs::read(&model_path).unwrap(), payload);
+        assert!(model_integrity::checksum_path(&model_path).exists());
+        assert!(seen.lock().unwrap()[0].0.starts_with("/missing/"));
+
+        // A partial file is resumed with a Range request
+        write_config(&config_file, serde_json::json!({ "model_mirrors": [good] }));
+        start_over(&payload[..40_000]);
+        download().unwrap();
+        assert_eq!(fs::read(&model_path).unwrap(), payload);
+        assert_eq!(seen.lock().unwrap()[0].1.as_deref(), Some("bytes=40000-"));
+
+        // A partial file that is already complete gets 416 and is verified as is
+        start_over(&payload);
+        download().unwrap();
+        assert_eq!(fs::read(&model_path).unwrap(), payload);
+        assert!(!part.exists());
+
+        // Requests go through the configured proxy
+        write_config(
+            &config_file,
+            serde_json::json!({
+                "model_mirrors": ["http://models.invalid/good/"],
+                "proxy": base,
+            }),
+        );
+        start_over(&[]);
+        download().unwrap();
+        assert_eq!(fs::read(&model_path).unwrap(), payload);
+        assert!(seen.lock().unwrap()[0]
+            .0
+            .starts_with("http://models.invalid/"));
+
+        // A pinned checksum wins over the one the mirror sends
+        write_config(&config_file, serde_json::json!({ "model_mirrors": [good] }));
+        start_over(&[]);
+        let pinned = ModelInfo {
+            sha256: Some("0000000000000000000000000000000000000000000000000000000000000000"),
+            ..TEST_MODEL
+        };
+        let error =
+            WhisperTranscriber::download_from_mirrors(&pinned, &|_, _| {}, &AtomicBool::new(false))
+                .unwrap_err();
+        assert!(error.contains("Checksum mismatch"), "{}", error);
+        assert!(!model_path.exists() && !part.exists());
+
+        // Offline mode explains where to put the file instead of downloading
+        write_config(&config_file, serde_json::json!({ "offline": true }));
+        start_over(&[]);
+        let error = download().unwrap_err();
+        assert!(error.starts_with("Offline mode"), "{}", error);
+        assert!(error.contains(TEST_MODEL.file_name), "{}", error);
+        assert!(seen.lock().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,457 @@
+use serde_json::{json, Map, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+use crate::config::{self, WyomingServer};
+use crate::local_models;
+use crate::model_cache::ModelCache;
+use crate::transcriber_utils::{ensure_transcriber_for, transcription_slot};
+
+/// Wyoming protocol version written in event headers
+const WYOMING_VERSION: &str = "1.5.2";
+
+// Languages offered in `info`; whisper detects the language when none is requested
+const LANGUAGES: &[&str] = &[
+    "en", "ru", "de", "fr", "es", "it", "pt", "nl", "pl", "uk", "tr", "zh", "ja", "ko", "ar",
+    "hi", "sv", "fi", "cs", "el",
+];
+
+// Largest JSON data block or payload accepted in one event (same as the API server's uploads)
+const MAX_EVENT_BYTES: usize = 100 * 1024 * 1024;
+
+// Sample rates accepted from clients; resampling to 16 kHz from far outside this range
+// would need absurd buffers
+const SAMPLE_RATES: std::ops::RangeInclusive<u32> = 4_000..=384_000;
+const MAX_CHANNELS: u16 = 8;
+
+// One Wyoming event: JSON header line, optional JSON data and optional binary payload
+struct Event {
+    kind: String,
+    data: Map<String, Value>,
+    payload: Vec<u8>,
+}
+
+// Audio format of the chunks of the current utterance
+struct AudioFormat {
+    rate: u32,
+    width: u16,
+    channels: u16,
+}
+
+/// Start the Wyoming ASR server if enabled in config.
+/// It transcribes with the models in `model_cache`, queued with hotkey dictation.
+pub fn start_if_enabled(model_cache: Arc<ModelCache>) {
+    let settings = config::get_wyoming_server();
+    if !settings.enabled {
+        return;
+    }
+
+    let addr = format!("{}:{}", settings.host, settings.port);
+    let listener = match TcpListener::bind(&addr) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to start Wyoming server on {}: {}", addr, e);
+            return;
+        }
+    };
+    println!("Wyoming ASR server listening on tcp://{}", addr);
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let model_cache = model_cache.clone();
+                    let settings = settings.clone();
+                    thread::spawn(move || {
+                        let peer = stream
+                            .peer_addr()
+                            .map(|a| a.to_string())
+                            .unwrap_or_default();
+                        if let Err(e) = handle_connection(stream, &settings, &model_cache) {
+                            eprintln!("Wyoming connection {} closed: {}", peer, e);
+                        }
+                    });
+                }
+                Err(e) => eprintln!("Wyoming connection failed: {}", e),
+            }
+        }
+    });
+}
+
+fn read_event(reader: &mut impl BufRead) -> Result<Option<Event>, String> {
+    let mut line = String::new();
+    let n = reader
+        .read_line(&mut line)
+        .map_err(|e| format!("read failed: {}", e))?;
+    if n == 0 {
+        return Ok(None);
+    }
+    let header: Value =
+        serde_json::from_str(line.trim()).map_err(|e| format!("invalid event header: {}", e))?;
+    let kind = header
+        .get("type")
+        .and_then(|t| t.as_str())
+        .ok_or("event without type")?
+        .to_string();
+
+    // Data may be inline in the header and/or follow it as a separate JSON block
+    let mut data = header
+        .get("data")
+        .and_then(|d| d.as_object())
+        .cloned()
+        .unwrap_or_default();
+    let length = |key: &str| -> Result<usize, String> {
+        let length = header.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        if length > MAX_EVENT_BYTES as u64 {
+            return Err(format!("{} of {} bytes is too large", key, length));
+        }
+        Ok(length as usize)
+    };
+    let data_length = length("data_length")?;
+    let payload_length = length("payload_length")?;
+    if data_length > 0 {
+        let mut buf = vec![0u8; data_length];
+        reader
+            .read_exact(&mut buf)
+            .map_err(|e| format!("read failed: {}", e))?;
+        if let Ok(Value::Object(extra)) = serde_json::from_slice(&buf) {
+            data.extend(extra);
+        }
+    }
+    let mut payload = vec![0u8; payload_length];
+    reader
+        .read_exact(&mut payload)
+        .map_err(|e| format!("read failed: {}", e))?;
+
+    Ok(Some(Event {
+        kind,
+        data,
+        payload,
+    }))
+}
+
+fn write_event(stream: &mut impl Write, kind: &str, data: Value) -> Result<(), String> {
+    let data = data.to_string();
+    let header = json!({
+        "type": kind,
+        "version": WYOMING_VERSION,
+        "data_length": data.len(),
+    });
+    let mut message = header.to_string().into_bytes();
+    message.push(b'\n');
+    message.extend_from_slice(data.as_bytes());
+    stream
+        .write_all(&message)
+        .map_err(|e| format!("write failed: {}", e))
+}
+
+// The model the server transcribes with: configured override or the selected model
+fn server_model(settings: &WyomingServer) -> String {
+    settings
+        .model
+        .clone()
+        .filter(|m| !m.is_empty())
+        .unwrap_or_else(config::get_selected_model)
+}
+
+fn info(settings: &WyomingServer) -> Value {
+    let model = server_model(settings);
+    let languages: Vec<&str> = match settings.language.as_deref() {
+        Some(lang) if !lang.is_empty() => vec![lang],
+        _ => LANGUAGES.to_vec(),
+    };
+    let attribution = json!({
+        "name": "OpenAI Whisper / whisper.cpp",
+        "url": "https://github.com/ggerganov/whisper.cpp",
+    });
+    json!({
+        "asr": [{
+            "name": "voice_input",
+            "description": "Local Whisper transcription from voice_input",
+            "attribution": attribution,
+            "installed": true,
+            "version": env!("CARGO_PKG_VERSION"),
+            "models": [{
+                "name": model,
+                "description": format!("Whisper {}", model),
+                "attribution": attribution,
+                "installed": true,
+                "languages": languages,
+                "version": env!("CARGO_PKG_VERSION"),
+            }],
+        }],
+    })
+}
+
+// The format in `data`: None when it doesn't specify one, an error when it is unusable
+fn audio_format(data: &Map<String, Value>) -> Result<Option<AudioFormat>, String> {
+    let field = |key: &str| data.get(key).and_then(|v| v.as_u64());
+    let (Some(rate), Some(width), Some(channels)) =
+        (field("rate"), field("width"), field("channels"))
+    else {
+        return Ok(None);
+    };
+    let rate = u32::try_from(rate)
+        .ok()
+        .filter(|r| SAMPLE_RATES.contains(r))
+        .ok_or_else(|| format!("unsupported sample rate {}", rate))?;
+    let width = match width {
+        1 | 2 | 4 => width as u16,
+        other => return Err(format!("unsupported sample width {}", other)),
+    };
+    let channels = u16::try_from(channels)
+        .ok()
+        .filter(|c| (1..=MAX_CHANNELS).contains(c))
+        .ok_or_else(|| format!("unsupported channel count {}", channels))?;
+    Ok(Some(AudioFormat {
+        rate,
+        width,
+        channels,
+    }))
+}
+
+// Little-endian PCM of 1, 2 or 4 bytes per sample to f32
+fn pcm_to_f32(bytes: &[u8], width: u16, out: &mut Vec<f32>) -> Result<(), String> {
+    match width {
+        1 => out.extend(bytes.iter().map(|&b| (b as f32 - 128.0) / 128.0)),
+        2 => out.extend(
+            bytes
+                .chunks_exact(2)
+                .map(|c| i16::from_le_bytes([c[0], c[1]]) as f32 / 32768.0),
+        ),
+        4 => out.extend(
+            bytes
+                .chunks_exact(4)
+                .map(|c| i32::from_le_bytes([c[0], c[1], c[2], c[3]]) as f32 / 2147483648.0),
+        ),
+        other => return Err(format!("unsupported sample width {}", other)),
+    }
+    Ok(())
+}
+
+fn transcribe(
+    settings: &WyomingServer,
+    requested_language: Option<&str>,
+    samples: &[f32],
+    format: &AudioFormat,
+    model_cache: &ModelCache,
+) -> Result<String, String> {
+    // Configured language wins over the client's request; none means auto-detect
+    let language = settings
+        .language
+        .as_deref()
+        .or(requested_language)
+        .filter(|l| !l.is_empty());
+    let is_english = language.is_some_and(|l| l.starts_with("en"));
+    let (en_file, multi_file) = local_models::model_files(&server_model(settings));
+    let model_file = if is_english { en_file } else { multi_file };
+
+    let _slot = transcription_slot();
+    let transcriber = ensure_transcriber_for(is_english, &model_file, model_cache)?;
+    transcriber.transcribe_samples(samples, format.rate, format.channels, language)
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    settings: &WyomingServer,
+    model_cache: &ModelCache,
+) -> Result<(), String> {
+    let mut writer = stream
+        .try_clone()
+        .map_err(|e| format!("failed to clone stream: {}", e))?;
+    let mut reader = BufReader::new(stream);
+
+    let mut requested_language: Option<String> = None;
+    let mut format: Option<AudioFormat> = None;
+    let mut samples: Vec<f32> = Vec::new();
+
+    while let Some(event) = read_event(&mut reader)? {
+        match event.kind.as_str() {
+            "describe" => write_event(&mut writer, "info", info(settings))?,
+            "transcribe" => {
+                requested_language = event
+                    .data
+                    .get("language")
+                    .and_then(|l| l.as_str())
+                    .map(|l| l.to_string());
+            }
+            "audio-start" => {
+                format = audio_format(&event.data)?;
+                samples.clear();
+            }
+            "audio-chunk" => {
+                // Chunks carry their own format; the first one defines the utterance format
+                if format.is_none() {
+                    format = audio_format(&event.data)?;
+                }
+                let width = format.as_ref().map(|f| f.width).unwrap_or(2);
+                pcm_to_f32(&event.payload, width, &mut samples)?;
+            }
+            "audio-stop" => {
+                let text = match &format {
+                    Some(format) if !samples.is_empty() => {
+                        println!(
+                            "Wyoming: transcribing {} samples at {} Hz",
+                            samples.len(),
+                            format.rate
+                        );
+                        transcribe(
+                            settings,
+                            requested_language.as_deref(),
+                            &samples,
+                            format,
+                            model_cache,
+                        )
+                        .unwrap_or_else(|e| {
+                            eprintln!("Wyoming transcription failed: {}", e);
+                            String::new()
+                        })
+                    }
+                    _ => String::new(),
+                };
+                let text = text.lines().map(str::trim).collect::<Vec<_>>().join(" ");
+                write_event(&mut writer, "transcript", json!({ "text": text.trim() }))?;
+                samples.clear();
+                format = None;
+                requested_language = None;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn read_all(bytes: &[u8]) -> Result<Vec<Event>, String> {
+        let mut reader = Cursor::new(bytes);
+        let mut events = Vec::new();
+        while let Some(event) = read_event(&mut reader)? {
+            events.push(event);
+        }
+        Ok(events)
+    }
+
+    #[test]
+    fn event_framing() {
+        let mut bytes = Vec::new();
+        write_event(&mut bytes, "transcript", json!({ "text": "hello" })).unwrap();
+        bytes.extend_from_slice(
+            b"{\"type\": \"audio-chunk\", \"data\": {\"rate\": 16000}, \"data_length\": 28, \"payload_length\": 4}\n",
+        );
+        bytes.extend_from_slice(b"{\"width\": 2, \"channels\": 1}\n\x01\x02\x03\x04");
+        bytes.extend_from_slice(b"{\"type\": \"audio-stop\"}\n");
+
+        let events = read_all(&bytes).unwrap();
+        let kinds: Vec<&str> = events.iter().map(|e| e.kind.as_str()).collect();
+        assert_eq!(kinds, ["transcript", "audio-chunk", "audio-stop"]);
+        assert_eq!(events[0].data["text"], "hello");
+        // Inline data and the separate data block are merged
+        let chunk = &events[1];
+        assert_eq!(
+            (
+                &chunk.data["rate"],
+                &chunk.data["width"],
+                &chunk.data["channels"]
+            ),
+            (&json!(16000), &json!(2), &json!(1))
+        );
+        assert_eq!(chunk.payload, [1, 2, 3, 4]);
+        assert!(events[2].data.is_empty() && events[2].payload.is_empty());
+    }
+
+    #[test]
+    fn malformed_events() {
+        let too_large = MAX_EVENT_BYTES + 1;
+        let cases = [
+            "not json\n".to_string(),
+            "{\"data\": {}}\n".to_string(),
+            format!(
+                "{{\"type\": \"audio-chunk\", \"payload_length\": {}}}\n",
+                too_large
+            ),
+            format!(
+                "{{\"type\": \"describe\", \"data_length\": {}}}\n",
+                too_large
+            ),
+            format!(
+                "{{\"type\": \"audio-chunk\", \"payload_length\": {}}}\n",
+                u64::MAX
+            ),
+            // Payload shorter than announced
+            "{\"type\": \"audio-chunk\", \"payload_length\": 8}\n\x01\x02".to_string(),
+        ];
+        for case in &cases {
+            assert!(read_all(case.as_bytes()).is_err(), "{:?}", case);
+        }
+    }
+
+    #[test]
+    fn audio_formats() {
+        let format = |data: Value| {
+            audio_format(data.as_object().unwrap())
+                .map(|f| f.map(|f| (f.rate, f.width, f.channels)))
+        };
+        let cases = [
+            (
+                json!({ "rate": 16000, "width": 2, "channels": 1 }),
+                Ok(Some((16000, 2, 1))),
+            ),
+            (
+                json!({ "rate": 48000, "width": 4, "channels": 2 }),
+                Ok(Some((48000, 4, 2))),
+            ),
+            (
+                json!({ "rate": 8000, "width": 1, "channels": 8 }),
+                Ok(Some((8000, 1, 8))),
+            ),
+            (json!({ "rate": 16000 }), Ok(None)),
+            (json!({}), Ok(None)),
+        ];
+        for (data, expected) in cases {
+            assert_eq!(format(data.clone()), expected, "{}", data);
+        }
+        let invalid = [
+            json!({ "rate": 0, "width": 2, "channels": 1 }),
+            json!({ "rate": 4294983296u64, "width": 2, "channels": 1 }),
+            json!({ "rate": 16000, "width": 3, "channels": 1 }),
+            json!({ "rate": 16000, "width": 0, "channels": 1 }),
+            json!({ "rate": 16000, "width": 65538, "channels": 1 }),
+            json!({ "rate": 16000, "width": 2, "channels": 0 }),
+            json!({ "rate": 16000, "width": 2, "channels": 65537 }),
+        ];
+        for data in invalid {
+            assert!(format(data.clone()).is_err(), "{}", data);
+        }
+    }
+
+    #[test]
+    fn pcm_conversion() {
+        let cases: [(u16, &[u8], &[f32]); 4] = [
+            (1, &[0, 128, 255], &[-1.0, 0.0, 127.0 / 128.0]),
+            (
+                2,
+                &[0x00, 0x80, 0x00, 0x00, 0xff, 0x7f],
+                &[-1.0, 0.0, 32767.0 / 32768.0],
+            ),
+            (
+                4,
+                &[0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x40],
+                &[-1.0, 0.5],
+            ),
+            // A trailing partial sample is ignored
+            (2, &[0x00, 0x40, 0x01], &[0.5]),
+        ];
+        for (width, bytes, expected) in cases {
+            let mut out = Vec::new();
+            pcm_to_f32(bytes, width, &mut out).unwrap();
+            assert_eq!(out, expected, "width {}", width);
+        }
+        assert!(pcm_to_f32(&[0, 0, 0], 3, &mut Vec::new()).is_err());
+    }
+}